@@ -19,7 +19,7 @@ use soroban_client::transaction::{AccountBehavior, Transaction, TransactionBehav
 use soroban_client::transaction::TransactionBuilderBehavior;
 use soroban_client::transaction_builder::TransactionBuilder;
 use tokio::time::{sleep, Duration};
-use tracing::{info, error};
+use tracing::{info, error, warn};
 use tracing_subscriber;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -48,6 +48,23 @@ struct Filter {
 struct EncryptedInputSubmittedEvent {
     batch_id: u64,
     submitter: String,
+    index: u32,
+    nonce: u64,
+    data_ref: Option<DataReference>,
+}
+
+/// Off-chain ciphertext of an input submitted in data-availability mode
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct DataReference {
+    data_hash: String,
+    length: u32,
+    locator: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct InputWithdrawnEvent {
+    batch_id: u64,
+    index: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -55,22 +72,37 @@ struct NewBatchCreatedEvent {
     batch_id: u64,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct BatchAssignedEvent {
+    batch_id: u64,
+    primary: String,
+    backup: String,
+    assigned_at: u32,
+    window: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum EventTopics {
     ENCRYPTED_INPUT_SUBMITTED,
     NEW_BATCH_CREATED,
+    INPUT_WITHDRAWN,
+    BATCH_ASSIGNED,
 }
 
 // Constants
 const ATTESTATION_SERVICE_CONTRACT_ID: &str = "CD6OMWI5REMXRH4LPWHNU6CZJ6LDZBUC7V4TAY532A6PTRQLK5YRCXCZ";
 const RPC_URL: &str = "https://soroban-testnet.stellar.org";
 const FEE: u32 = 100;
+// Same cap as MAX_INPUT_REFERENCE_SIZE in the contract
+const MAX_INPUT_REFERENCE_SIZE: u32 = 1_048_576;
 
 struct Config {
     public_key: String,
     secret_key: String,
     tee_pubkey: String,
+    tee_secret_key: String,
     tee_management_contract_id: String,
+    da_store_url: String,
 }
 
 impl Config {
@@ -90,10 +122,21 @@ impl Config {
                 .expect("TEE_PUBKEY must be set in .env file")
                 .trim()
                 .to_string(),
+            // ed25519 attestation key of the TEE as a Stellar secret seed (S...)
+            tee_secret_key: env::var("TEE_SECRET_KEY")
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
             tee_management_contract_id: env::var("TEE_MANAGEMENT_CONTRACT_ID")
                 .unwrap_or_default()
                 .trim()
                 .to_string(),
+            // Content-addressed store serving ciphertexts at <url>/<sha256 hex>
+            da_store_url: env::var("DA_STORE_URL")
+                .unwrap_or_default()
+                .trim()
+                .trim_end_matches('/')
+                .to_string(),
         }
     }
 }
@@ -126,6 +169,8 @@ fn listen_for_events_rpc(
 ) -> (
     Vec<EncryptedInputSubmittedEvent>,
     Vec<NewBatchCreatedEvent>,
+    Vec<InputWithdrawnEvent>,
+    Vec<BatchAssignedEvent>,
 ) {
     let client = Client::new();
     let url = RPC_URL;
@@ -184,11 +229,18 @@ fn send_rpc_request(client: &Client, url: &str, request: &RpcRequest) -> String
 fn process_events(
     response_body: String,
     read_event: &EventTopics,
-) -> (Vec<EncryptedInputSubmittedEvent>, Vec<NewBatchCreatedEvent>) {
+) -> (
+    Vec<EncryptedInputSubmittedEvent>,
+    Vec<NewBatchCreatedEvent>,
+    Vec<InputWithdrawnEvent>,
+    Vec<BatchAssignedEvent>,
+) {
     let response_json: Value =
         serde_json::from_str(&response_body).expect("Failed to parse response body");
     let mut encrypted_input_events = Vec::new();
     let mut new_batch_events = Vec::new();
+    let mut input_withdrawn_events = Vec::new();
+    let mut batch_assigned_events = Vec::new();
 
     if let Some(events) = response_json["result"]["events"].as_array() {  
         for event in events {
@@ -203,17 +255,27 @@ fn process_events(
             let should_process = match read_event {
                 EventTopics::ENCRYPTED_INPUT_SUBMITTED => {
                     topic_string == Some("ENCRYPTED_INPUT_SUBMITTED")
+                        || topic_string == Some("ENCRYPTED_INPUTS_SUBMITTED")
                 }
                 EventTopics::NEW_BATCH_CREATED => {
                     topic_string == Some("NEW_BATCH_CREATED")
                 }
+                EventTopics::INPUT_WITHDRAWN => {
+                    topic_string == Some("INPUT_WITHDRAWN")
+                }
+                EventTopics::BATCH_ASSIGNED => {
+                    topic_string == Some("BATCH_ASSIGNED")
+                }
             };
             
             if should_process {
                 if let Some(vec_data) = event["valueJson"].get("vec").and_then(Value::as_array) {
                     match read_event {
                         EventTopics::ENCRYPTED_INPUT_SUBMITTED => {
-                            if let Some(parsed_event) = parse_encrypted_input_event(&vec_data) {
+                            if topic_string == Some("ENCRYPTED_INPUTS_SUBMITTED") {
+                                // One event covers every input of a multi-input submission
+                                encrypted_input_events.extend(parse_encrypted_inputs_event(&vec_data));
+                            } else if let Some(parsed_event) = parse_encrypted_input_event(&vec_data) {
                                 encrypted_input_events.push(parsed_event);
                             }
                         }
@@ -222,13 +284,28 @@ fn process_events(
                                 new_batch_events.push(parsed_event);
                             }
                         }
+                        EventTopics::INPUT_WITHDRAWN => {
+                            if let Some(parsed_event) = parse_input_withdrawn_event(&vec_data) {
+                                input_withdrawn_events.push(parsed_event);
+                            }
+                        }
+                        EventTopics::BATCH_ASSIGNED => {
+                            if let Some(parsed_event) = parse_batch_assigned_event(&vec_data) {
+                                batch_assigned_events.push(parsed_event);
+                            }
+                        }
                     }
                 }
             }
         }
     }
 
-    (encrypted_input_events, new_batch_events)
+    (
+        encrypted_input_events,
+        new_batch_events,
+        input_withdrawn_events,
+        batch_assigned_events,
+    )
 }
 
 fn parse_encrypted_input_event(event_arr: &[Value]) -> Option<EncryptedInputSubmittedEvent> {
@@ -246,19 +323,86 @@ fn parse_encrypted_input_event(event_arr: &[Value]) -> Option<EncryptedInputSubm
             })
             .unwrap_or(0);
         
+        // The input index is the position of the commitment in the batch
+        let index = event_arr
+            .get(2)
+            .and_then(|v| v.get("u32"))
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+
+        // The submitter nonce orders a submitter's inputs and identifies replays
+        let nonce = event_arr
+            .get(3)
+            .and_then(|v| v.get("u64"))
+            .and_then(|v| {
+                v.as_u64().or_else(|| {
+                    v.as_str()
+                        .and_then(|s| s.parse::<u64>().ok())
+                })
+            })
+            .unwrap_or(0);
+
+        // Inputs submitted in data-availability mode carry a reference to
+        // their off-chain ciphertext: data_hash, length, locator
+        let data_ref = event_arr
+            .get(4)
+            .and_then(|v| v.get("bytes"))
+            .and_then(Value::as_str)
+            .map(|data_hash| DataReference {
+                data_hash: data_hash.to_string(),
+                length: event_arr
+                    .get(5)
+                    .and_then(|v| v.get("u32"))
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0) as u32,
+                locator: event_arr
+                    .get(6)
+                    .and_then(|v| v.get("string"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+            });
+
         Some(EncryptedInputSubmittedEvent {
             batch_id,
             submitter: event_arr[1]
-                .get("string")
+                .get("address")
                 .and_then(Value::as_str)
                 .unwrap_or("")
                 .to_string(),
+            index,
+            nonce,
+            data_ref,
         })
     } else {
         None
     }
 }
 
+/// Expand an ENCRYPTED_INPUTS_SUBMITTED event
+/// (batch_id, submitter, first_index, first_nonce, count) into one event per input
+fn parse_encrypted_inputs_event(event_arr: &[Value]) -> Vec<EncryptedInputSubmittedEvent> {
+    let first = match parse_encrypted_input_event(event_arr) {
+        Some(first) => first,
+        None => return Vec::new(),
+    };
+    let count = event_arr
+        .get(4)
+        .and_then(|v| v.get("u32"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+
+    (0..count)
+        .map(|offset| EncryptedInputSubmittedEvent {
+            batch_id: first.batch_id,
+            submitter: first.submitter.clone(),
+            index: first.index + offset,
+            nonce: first.nonce + offset as u64,
+            data_ref: None,
+        })
+        .collect()
+}
+
 fn parse_new_batch_event(event_arr: &[Value]) -> Option<NewBatchCreatedEvent> {
     if event_arr.len() >= 1 {
         // Handle both string and numeric u64 values
@@ -282,6 +426,78 @@ fn parse_new_batch_event(event_arr: &[Value]) -> Option<NewBatchCreatedEvent> {
     }
 }
 
+fn parse_input_withdrawn_event(event_arr: &[Value]) -> Option<InputWithdrawnEvent> {
+    if event_arr.len() >= 2 {
+        // Handle both string and numeric u64 values
+        let batch_id = event_arr[0]
+            .get("u64")
+            .and_then(|v| {
+                // Try as u64 first
+                v.as_u64().or_else(|| {
+                    // If that fails, try as string and parse
+                    v.as_str()
+                        .and_then(|s| s.parse::<u64>().ok())
+                })
+            })
+            .unwrap_or(0);
+        let index = event_arr[1]
+            .get("u32")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+
+        Some(InputWithdrawnEvent {
+            batch_id,
+            index,
+        })
+    } else {
+        None
+    }
+}
+
+fn parse_batch_assigned_event(event_arr: &[Value]) -> Option<BatchAssignedEvent> {
+    if event_arr.len() >= 5 {
+        // Handle both string and numeric u64 values
+        let batch_id = event_arr[0]
+            .get("u64")
+            .and_then(|v| {
+                // Try as u64 first
+                v.as_u64().or_else(|| {
+                    // If that fails, try as string and parse
+                    v.as_str()
+                        .and_then(|s| s.parse::<u64>().ok())
+                })
+            })
+            .unwrap_or(0);
+        let bytes_hex = |v: &Value| {
+            v.get("bytes")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_lowercase()
+        };
+        let as_u32 = |v: &Value| v.get("u32").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+        Some(BatchAssignedEvent {
+            batch_id,
+            primary: bytes_hex(&event_arr[1]),
+            backup: bytes_hex(&event_arr[2]),
+            assigned_at: as_u32(&event_arr[3]),
+            window: as_u32(&event_arr[4]),
+        })
+    } else {
+        None
+    }
+}
+
+/// Mirrors the contract rule: the primary may attest right away, the backup
+/// once the primary window has passed, and anyone after a second window
+fn may_attest(assignment: &BatchAssignedEvent, tee_pubkey_hex: &str, latest_ledger: u32) -> bool {
+    let backup_from = assignment.assigned_at.saturating_add(assignment.window);
+    let open_from = backup_from.saturating_add(assignment.window);
+    assignment.primary == tee_pubkey_hex
+        || (assignment.backup == tee_pubkey_hex && latest_ledger >= backup_from)
+        || latest_ledger >= open_from
+}
+
 async fn process_batches(
     processed_batches: &mut HashSet<u64>,
     batch_inputs: &mut HashMap<u64, Vec<EncryptedInputSubmittedEvent>>,
@@ -321,16 +537,61 @@ async fn process_batches(
         "📥 Found {} encrypted input submissions",
         encrypted_input_events.0.len()
     );
+    // Listen for input withdrawals (no topic filter - filtered client-side)
+    let input_withdrawn_events = tokio::task::spawn_blocking(|| {
+        listen_for_events_rpc(
+            ATTESTATION_SERVICE_CONTRACT_ID,
+            &EventTopics::INPUT_WITHDRAWN,
+        )
+    })
+    .await
+    .expect("Failed to fetch input withdrawn events");
+
+    // Listen for batch assignments (no topic filter - filtered client-side)
+    let batch_assigned_events = tokio::task::spawn_blocking(|| {
+        listen_for_events_rpc(
+            ATTESTATION_SERVICE_CONTRACT_ID,
+            &EventTopics::BATCH_ASSIGNED,
+        )
+    })
+    .await
+    .expect("Failed to fetch batch assigned events");
+
     info!("📥 Found {} new batches", new_batch_events.1.len());
+    info!("📥 Found {} withdrawn inputs", input_withdrawn_events.2.len());
 
     // Accumulate inputs by batch ID
     for event in encrypted_input_events.0 {
-        batch_inputs
+        let inputs = batch_inputs
             .entry(event.batch_id)
-            .or_insert_with(Vec::new)
-            .push(event);
+            .or_insert_with(Vec::new);
+
+        // The contract never accepts a nonce twice, so a known (submitter, nonce)
+        // pair is an event we have already seen in a previous cycle
+        if inputs
+            .iter()
+            .any(|input| input.submitter == event.submitter && input.nonce == event.nonce)
+        {
+            continue;
+        }
+
+        inputs.push(event);
+    }
+
+    // Exclude withdrawn inputs from processing
+    for event in input_withdrawn_events.2 {
+        if let Some(inputs) = batch_inputs.get_mut(&event.batch_id) {
+            inputs.retain(|input| input.index != event.index);
+        }
     }
 
+    let batch_assignments: HashMap<u64, BatchAssignedEvent> = batch_assigned_events
+        .3
+        .into_iter()
+        .map(|event| (event.batch_id, event))
+        .collect();
+    let tee_pubkey_hex = hex::encode(tee_pubkey_bytes(config));
+
     // Process batches that are ready for attestation
     for new_batch_event in new_batch_events.1 {
         let batch_id = new_batch_event.batch_id;
@@ -345,6 +606,21 @@ async fn process_batches(
             continue;
         }
 
+        // Only attest while this TEE holds the rights for the batch
+        if let Some(assignment) = batch_assignments.get(&previous_batch_id) {
+            let latest_ledger = match server.get_latest_ledger().await {
+                Ok(ledger) => ledger.sequence,
+                Err(e) => {
+                    error!("Failed to fetch latest ledger: {:?}", e);
+                    continue;
+                }
+            };
+            if !may_attest(assignment, &tee_pubkey_hex, latest_ledger) {
+                info!("⏳ Batch {} is assigned to another TEE, waiting for the assignment window", previous_batch_id);
+                continue;
+            }
+        }
+
         // Check if we have inputs for this batch
         if let Some(inputs) = batch_inputs.get(&previous_batch_id) {
             info!(
@@ -361,7 +637,7 @@ async fn process_batches(
             }
 
             // Process the batch (decrypt and compute in TEE)
-            match process_batch_inputs(&server, previous_batch_id, inputs).await {
+            match process_batch_inputs(&server, previous_batch_id, inputs, config).await {
                 Ok(state_root) => {
                     // Submit attestation
                     if let Ok(_) = submit_attestation(
@@ -401,9 +677,26 @@ async fn process_batch_inputs(
     _server: &Server,
     batch_id: u64,
     inputs: &[EncryptedInputSubmittedEvent],
+    config: &Config,
 ) -> Result<[u8; 32], String> {
     info!("Processing batch {} with {} encrypted inputs", batch_id, inputs.len());
 
+    // Fetch the ciphertexts of inputs submitted in data-availability mode
+    // An input whose ciphertext cannot be fetched or verified is dropped so it
+    // does not stall the rest of the batch
+    let mut off_chain_inputs: Vec<(u32, Vec<u8>)> = Vec::new();
+    for input in inputs {
+        if let Some(data_ref) = &input.data_ref {
+            match fetch_off_chain_input(data_ref, config).await {
+                Ok(ciphertext) => off_chain_inputs.push((input.index, ciphertext)),
+                Err(e) => warn!(
+                    "Skipping input {} of batch {}: {}",
+                    input.index, batch_id, e
+                ),
+            }
+        }
+    }
+
     // TODO: In a real TEE implementation:
     // 1. Fetch encrypted inputs from the contract using batch_inputs(batch_id)
     // 2. Decrypt inputs inside the TEE (using TEE-specific decryption keys)
@@ -418,6 +711,7 @@ async fn process_batch_inputs(
     let mut hasher = DefaultHasher::new();
     batch_id.hash(&mut hasher);
     inputs.len().hash(&mut hasher);
+    off_chain_inputs.hash(&mut hasher);
     let hash = hasher.finish();
 
     let mut state_root = [0u8; 32];
@@ -429,6 +723,51 @@ async fn process_batch_inputs(
     Ok(state_root)
 }
 
+/// Fetch an off-chain ciphertext and check it against its on-chain hash and
+/// length before it is decrypted
+/// The ciphertext is always looked up by hash in the content-addressed store at
+/// DA_STORE_URL, the contract only accepts empty locators, and the read
+/// stops as soon as the body exceeds the committed length
+/// The committed length is set by the submitter, so it is capped and never
+/// used to preallocate
+async fn fetch_off_chain_input(data_ref: &DataReference, config: &Config) -> Result<Vec<u8>, String> {
+    use sha2::{Sha256, Digest};
+
+    if config.da_store_url.is_empty() {
+        return Err(format!("No DA_STORE_URL set to fetch input {}", data_ref.data_hash));
+    }
+    let data_hash = data_ref.data_hash.to_lowercase();
+    if data_hash.len() != 64 || !data_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid data hash {}", data_ref.data_hash));
+    }
+    if data_ref.length > MAX_INPUT_REFERENCE_SIZE {
+        return Err(format!("Input {} is larger than {} bytes", data_hash, MAX_INPUT_REFERENCE_SIZE));
+    }
+    let url = format!("{}/{}", config.da_store_url, data_hash);
+
+    let mut response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("Failed to fetch input from {}: {:?}", url, e))?;
+    let max_len = data_ref.length as usize;
+    let mut ciphertext = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read input from {}: {:?}", url, e))?
+    {
+        if ciphertext.len() + chunk.len() > max_len {
+            return Err(format!("Input fetched from {} exceeds its length", url));
+        }
+        ciphertext.extend_from_slice(&chunk);
+    }
+
+    if ciphertext.len() != max_len || hex::encode(Sha256::digest(&ciphertext)) != data_hash {
+        return Err(format!("Input fetched from {} does not match its hash", url));
+    }
+
+    Ok(ciphertext)
+}
+
 async fn submit_attestation(
     server: &Server, 
     batch_id: u64,
@@ -453,28 +792,26 @@ async fn submit_attestation(
         .expect("Failed to convert state root to BytesM");
     let state_root_scval = ScVal::Bytes(state_root_bytes.into());
 
-    // Convert TEE pubkey to ScVal (32 bytes)
-    // TEE_PUBKEY can be either:
-    // 1. A 64-character hex string (32 bytes) - will be decoded directly
-    // 2. A Stellar address string - will be hashed to 32 bytes
+    // The mock computation has no public output, so commit to the empty output
     use sha2::{Sha256, Digest};
-    let tee_pubkey_bytes: [u8; 32] = if config.tee_pubkey.len() == 64 {
-        // Try hex decoding first (for hex strings)
-        hex::decode(&config.tee_pubkey)
-            .ok()
-            .and_then(|v| v.try_into().ok())
-            .unwrap_or_else(|| {
-                // If hex decode fails, hash the string to get 32 bytes
-                let mut hasher = Sha256::new();
-                hasher.update(config.tee_pubkey.as_bytes());
-                hasher.finalize().into()
-            })
-    } else {
-        // Hash the string to get 32 bytes
-        let mut hasher = Sha256::new();
-        hasher.update(config.tee_pubkey.as_bytes());
-        hasher.finalize().into()
-    };
+    let outputs_hash: [u8; 32] = Sha256::digest([]).into();
+    let outputs_hash_bytes: BytesM<{ u32::MAX }> = BytesM::try_from(outputs_hash.as_slice())
+        .expect("Failed to convert outputs hash to BytesM");
+    let outputs_hash_scval = ScVal::Bytes(outputs_hash_bytes.into());
+
+    // Sign batch_id || state_root || outputs_hash with the TEE attestation key
+    let tee_keypair = tee_keypair(config).ok_or_else(|| {
+        SorobanError::JsonError("TEE_SECRET_KEY must be set to sign attestations".to_string())
+    })?;
+    let signature = tee_keypair
+        .sign(&attestation_message(batch_id, &state_root, &outputs_hash))
+        .map_err(|e| SorobanError::JsonError(format!("Failed to sign attestation: {:?}", e)))?;
+    let signature_bytes: BytesM<{ u32::MAX }> = BytesM::try_from(signature.as_slice())
+        .expect("Failed to convert signature to BytesM");
+    let signature_scval = ScVal::Bytes(signature_bytes.into());
+
+    // Convert TEE pubkey to ScVal (32 bytes)
+    let tee_pubkey_bytes = tee_pubkey_bytes(config);
     let tee_pubkey_bytes_m: BytesM<{ u32::MAX }> = BytesM::try_from(tee_pubkey_bytes.as_slice())
         .expect("Failed to convert TEE pubkey to BytesM");
     let tee_pubkey_scval = ScVal::Bytes(tee_pubkey_bytes_m.into());
@@ -484,6 +821,9 @@ async fn submit_attestation(
         batch_id,
         tee_pubkey_scval,
         state_root_scval,
+        ScVal::Void,
+        outputs_hash_scval,
+        signature_scval,
     );
 
     let mut prepared_tx = match server.prepare_transaction(transaction).await {
@@ -569,11 +909,59 @@ async fn submit_attestation(
     }
 }
 
+/// The TEE attestation keypair, if TEE_SECRET_KEY is set
+fn tee_keypair(config: &Config) -> Option<Keypair> {
+    if config.tee_secret_key.is_empty() {
+        return None;
+    }
+    Keypair::from_secret(&config.tee_secret_key).ok()
+}
+
+/// Message the TEE signs to attest a batch:
+/// batch_id (8 bytes BE) || state_root || outputs_hash
+fn attestation_message(batch_id: u64, state_root: &[u8; 32], outputs_hash: &[u8; 32]) -> Vec<u8> {
+    let mut message = batch_id.to_be_bytes().to_vec();
+    message.extend_from_slice(state_root);
+    message.extend_from_slice(outputs_hash);
+    message
+}
+
+/// The raw public key of TEE_SECRET_KEY when it is set, otherwise TEE_PUBKEY,
+/// which can be either:
+/// 1. A 64-character hex string (32 bytes) - will be decoded directly
+/// 2. A Stellar address string - will be hashed to 32 bytes
+fn tee_pubkey_bytes(config: &Config) -> [u8; 32] {
+    use sha2::{Sha256, Digest};
+    if let Some(keypair) = tee_keypair(config) {
+        return keypair.raw_pubkey();
+    }
+    if config.tee_pubkey.len() == 64 {
+        // Try hex decoding first (for hex strings)
+        hex::decode(&config.tee_pubkey)
+            .ok()
+            .and_then(|v| v.try_into().ok())
+            .unwrap_or_else(|| {
+                // If hex decode fails, hash the string to get 32 bytes
+                let mut hasher = Sha256::new();
+                hasher.update(config.tee_pubkey.as_bytes());
+                hasher.finalize().into()
+            })
+    } else {
+        // Hash the string to get 32 bytes
+        let mut hasher = Sha256::new();
+        hasher.update(config.tee_pubkey.as_bytes());
+        hasher.finalize().into()
+    }
+}
+
 fn build_attestation_transaction(
     source_account: Rc<RefCell<Account>>,
     batch_id: u64,
     tee_pubkey: ScVal,
     state_root: ScVal,
+    public_output: ScVal,
+    outputs_hash: ScVal,
+    signature: ScVal,
 ) -> Transaction {
     let attestation_contract = Contracts::new(ATTESTATION_SERVICE_CONTRACT_ID).unwrap();
 
@@ -585,6 +973,9 @@ fn build_attestation_transaction(
                 tee_pubkey,
                 ScVal::U64(batch_id),
                 state_root,
+                public_output,
+                outputs_hash,
+                signature,
             ]),
         ))
         .build()
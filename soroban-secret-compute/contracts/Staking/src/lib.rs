@@ -1,17 +1,18 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, log, symbol_short, Address, Env, IntoVal, Map, String, Symbol, TryFromVal, Val, Vec};
+use shared::admin::{self, PendingAdmin};
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env};
 use soroban_token_sdk::TokenUtils;
 
 const MIN_STAKE_TOKENS: i128 = 1;
-const ASSETER_STORAGE_KEY: Symbol = symbol_short!("asserter");
-const TOKEN_SYMBOL: Symbol = symbol_short!("SOO");
+const BPS_DENOMINATOR: i128 = 10_000;
 
 #[contracttype]
 pub enum DataKey {
     Balance(Address),
     Staked(Address),
-    TokenAdmin
+    TokenAdmin,
+    Slasher(Address),
 }
 
 #[contract]
@@ -63,6 +64,79 @@ impl StakingContract {
     
         log!(&env, "User {} has unstaked {} tokens", user_addr, amount);
     }
+
+    pub fn staked(env: Env, user_addr: Address) -> i128 {
+        let key = DataKey::Staked(user_addr);
+        env.storage().persistent().get(&key).unwrap_or_default()
+    }
+
+    pub fn is_slasher(env: Env, slasher: Address) -> bool {
+        let key = DataKey::Slasher(slasher);
+        env.storage().persistent().get(&key).unwrap_or(false)
+    }
+
+    pub fn add_slasher(env: Env, slasher: Address) {
+        // Only the token admin can authorize slashers
+        TokenContract::get_token_admin(&env).require_auth();
+
+        let key = DataKey::Slasher(slasher.clone());
+        env.storage().persistent().set(&key, &true);
+
+        log!(&env, "Slasher {} has been added", slasher);
+    }
+
+    pub fn remove_slasher(env: Env, slasher: Address) {
+        TokenContract::get_token_admin(&env).require_auth();
+
+        let key = DataKey::Slasher(slasher.clone());
+        env.storage().persistent().remove(&key);
+
+        log!(&env, "Slasher {} has been removed", slasher);
+    }
+
+    /// Slash up to `amount` of the operator's stake. Staked tokens are held by
+    /// this contract; `challenger_share_bps` of the slashed amount goes to the
+    /// challenger and the rest to the treasury. Returns the slashed amount.
+    pub fn slash(
+        env: Env,
+        slasher: Address,
+        operator: Address,
+        amount: i128,
+        challenger: Address,
+        challenger_share_bps: u32,
+        treasury: Address,
+    ) -> i128 {
+        slasher.require_auth();
+
+        // Ensure the caller is an authorized slasher
+        if !Self::is_slasher(env.clone(), slasher.clone()) {
+            panic!("Unauthorized slasher");
+        }
+
+        if amount < 0 || challenger_share_bps as i128 > BPS_DENOMINATOR {
+            panic!("Invalid slash parameters");
+        }
+
+        // Slash at most what the operator has staked
+        let key = DataKey::Staked(operator.clone());
+        let staked_amt: i128 = env.storage().persistent().get(&key).unwrap_or_default();
+        let slashed = amount.min(staked_amt);
+        env.storage().persistent().set(&key, &(staked_amt - slashed));
+
+        // Pay out the slashed tokens
+        let challenger_amt = slashed * challenger_share_bps as i128 / BPS_DENOMINATOR;
+        let treasury_amt = slashed - challenger_amt;
+        let staking_contract = env.current_contract_address();
+        BalanceContract::spend_balance(&env, staking_contract.clone(), slashed);
+        BalanceContract::receive_balance(&env, challenger.clone(), challenger_amt);
+        BalanceContract::receive_balance(&env, treasury.clone(), treasury_amt);
+        TokenUtils::new(&env).events().transfer(staking_contract.clone(), challenger.clone(), challenger_amt);
+        TokenUtils::new(&env).events().transfer(staking_contract, treasury, treasury_amt);
+
+        log!(&env, "Operator {} has been slashed {} tokens", operator, slashed);
+
+        slashed
+    }
 }
 
 #[contractimpl] 
@@ -72,11 +146,32 @@ impl TokenContract {
         e.storage().instance().get(&key).unwrap() 
     } 
 
+    /// Set the token admin, can only be called once
+    /// Later changes go through propose_admin / accept_admin
     pub fn set_token_admin(e: &Env, id: Address) {
         let key = DataKey::TokenAdmin; 
+        if e.storage().instance().has(&key) || admin::admin_renounced(e) {
+            panic!("Token admin already set");
+        }
         e.storage().instance().set(&key, &id); 
     }
 
+    pub fn pending_admin(e: Env) -> Option<PendingAdmin> {
+        admin::pending_admin(&e)
+    }
+
+    pub fn propose_admin(e: Env, new_admin: Address) -> PendingAdmin {
+        admin::propose_admin(&e, &DataKey::TokenAdmin, new_admin)
+    }
+
+    pub fn accept_admin(e: Env) -> Address {
+        admin::accept_admin(&e, &DataKey::TokenAdmin)
+    }
+
+    pub fn renounce_admin(e: Env) {
+        admin::renounce_admin(&e, &DataKey::TokenAdmin)
+    }
+
     pub fn mint(e: Env, to: Address, amount: i128) {
         // TODO: have authorized minters 
         let admin = Self::get_token_admin(&e);
@@ -100,11 +195,7 @@ impl TokenContract {
 impl BalanceContract {
     pub fn read_balance(e: &Env, addr: Address) -> i128 {
         let key = DataKey::Balance(addr); 
-        if let Some(balance) = e.storage().persistent().get(&key) {
-            balance 
-        } else {
-            0
-        }
+        e.storage().persistent().get(&key).unwrap_or_default()
     }
 
     pub fn write_balance(e: &Env, addr: Address, amount: i128) {
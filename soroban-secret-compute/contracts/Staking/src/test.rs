@@ -1,12 +1,100 @@
 #![cfg(test)]
-use crate::{StakingContract, StakingContractClient};
-use soroban_sdk:: {log, symbol_short, testutils::Events, Env, String}; 
+use crate::{
+    BalanceContract, StakingContract, StakingContractClient, TokenContract, TokenContractClient,
+};
+use soroban_sdk::{testutils::Address as _, Address, Env};
 extern crate std;
 
 #[test]
 fn test() {
     let env = Env::default(); 
     let contract_id = env.register(StakingContract, ());
-    let client = StakingContractClient::new(&env, &contract_id);
+    let _client = StakingContractClient::new(&env, &contract_id);
    
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_slash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(StakingContract, ());
+    let client = StakingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let slasher = Address::generate(&env);
+    let challenger = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    // Fund the operator and bond its stake with this contract
+    env.as_contract(&contract_id, || {
+        TokenContract::set_token_admin(&env, admin.clone());
+        BalanceContract::write_balance(&env, operator.clone(), 1000);
+    });
+    client.stake(&operator, &contract_id, &1000);
+    assert_eq!(client.staked(&operator), 1000);
+
+    client.add_slasher(&slasher);
+    assert!(client.is_slasher(&slasher));
+
+    // Slash 400 with 25% going to the challenger
+    let slashed = client.slash(&slasher, &operator, &400, &challenger, &2500, &treasury);
+    assert_eq!(slashed, 400);
+    assert_eq!(client.staked(&operator), 600);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(BalanceContract::read_balance(&env, challenger.clone()), 100);
+        assert_eq!(BalanceContract::read_balance(&env, treasury.clone()), 300);
+        assert_eq!(BalanceContract::read_balance(&env, contract_id.clone()), 600);
+    });
+
+    // Slashing never takes more than the remaining stake
+    let slashed = client.slash(&slasher, &operator, &1000, &challenger, &0, &treasury);
+    assert_eq!(slashed, 600);
+    assert_eq!(client.staked(&operator), 0);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized slasher")]
+fn test_slash_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(StakingContract, ());
+    let client = StakingContractClient::new(&env, &contract_id);
+
+    let operator = Address::generate(&env);
+    let slasher = Address::generate(&env);
+    client.slash(&slasher, &operator, &100, &Address::generate(&env), &0, &Address::generate(&env));
+}
+
+#[test]
+fn test_token_admin_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(TokenContract, ());
+    let client = TokenContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    client.set_token_admin(&admin);
+
+    client.propose_admin(&new_admin);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_token_admin(), admin);
+
+    client.accept_admin();
+    assert_eq!(env.auths()[0].0, new_admin);
+    assert_eq!(client.get_token_admin(), new_admin);
+    assert_eq!(client.pending_admin(), None);
+}
+
+#[test]
+#[should_panic(expected = "Token admin already set")]
+fn test_set_token_admin_twice() {
+    let env = Env::default();
+    let contract_id = env.register(TokenContract, ());
+    let client = TokenContractClient::new(&env, &contract_id);
+
+    client.set_token_admin(&Address::generate(&env));
+    client.set_token_admin(&Address::generate(&env));
+}
@@ -1,8 +1,10 @@
 #![no_std]
 
+use shared::admin::{self, PendingAdmin};
+use shared::AttestationStatus;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, log, panic_with_error, symbol_short,
-    BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, vec,
+    Address, Bytes, BytesN, Env, IntoVal, InvokeError, Map, String, Symbol, Val, Vec,
 };
 
 const CURRENT_BATCH_ID: Symbol = symbol_short!("BATCHID");
@@ -10,6 +12,31 @@ const CURRENT_STATE_ROOT: Symbol = symbol_short!("STROOT");
 const BATCH_INPUTS: Symbol = symbol_short!("BATCHIN");
 const REGISTERED_TEES: Symbol = symbol_short!("TEES");
 const BATCH_ATTESTED: Symbol = symbol_short!("BATCHAT");
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const BATCH_LIMITS: Symbol = symbol_short!("LIMITS");
+const CURRENT_EPOCH: Symbol = symbol_short!("EPOCH");
+const ASSIGNMENT_WINDOW: Symbol = symbol_short!("WINDOW");
+const SUBSCRIBERS: Symbol = symbol_short!("SUBS");
+const SLASHING_CONFIG: Symbol = symbol_short!("SLASHCFG");
+const LATEST_FINALIZED_BATCH: Symbol = symbol_short!("LATEST");
+const ALLOWLIST_ENABLED: Symbol = symbol_short!("ALLOWLIST");
+const DATA_AVAILABILITY_MODE: Symbol = symbol_short!("DAMODE");
+const KEY_PUBLISHER: Symbol = symbol_short!("KEYPUB");
+
+/// Default number of ledgers a batch is reserved for its primary TEE
+const DEFAULT_ASSIGNMENT_WINDOW: u32 = 100;
+
+/// Maximum number of consumer contracts notified when a batch is attested
+const MAX_SUBSCRIBERS: u32 = 10;
+
+/// Maximum size in bytes of the public output published with an attestation
+const MAX_PUBLIC_OUTPUT_SIZE: u32 = 1024;
+
+/// Maximum size of a state blob stored inline with a checkpoint
+const MAX_STATE_BLOB_SIZE: u32 = 4096;
+
+/// Maximum size in bytes of an off-chain ciphertext referenced by an input
+const MAX_INPUT_REFERENCE_SIZE: u32 = 1_048_576;
 
 #[contract]
 pub struct OnchainCommitment;
@@ -18,8 +45,121 @@ pub struct OnchainCommitment;
 #[derive(Debug, Clone, PartialEq)]
 pub struct InputCommitment {
     pub encrypted_data: String,
-    pub submitter: String,
+    pub submitter: Address,
     pub timestamp: u64,
+    pub withdrawn: bool,
+    pub nonce: u64,
+    pub epoch: u64,
+}
+
+/// Off-chain ciphertext of an input submitted in data-availability mode
+/// Only the content-addressed store is supported: TEEs look the ciphertext up
+/// by its hash and the locator is always empty
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataReference {
+    pub data_hash: BytesN<32>,
+    pub length: u32,
+    pub locator: String,
+}
+
+/// X25519 public key that submitters encrypt inputs to during an epoch
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptionKey {
+    pub epoch: u64,
+    pub public_key: BytesN<32>,
+    pub tee_pubkey: BytesN<32>,
+    pub published_at: u64,
+    pub expires_at: u64,
+}
+
+/// Input caps applied to every batch, 0 means unlimited
+/// Withdrawn inputs keep counting toward the caps
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchLimits {
+    pub max_inputs_per_batch: u32,
+    pub max_inputs_per_submitter: u32,
+}
+
+/// TEEs assigned to attest a sealed batch
+/// The primary has exclusive rights until `assigned_at + window`, then the
+/// backup may also attest, and after a second window any registered TEE may
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchAssignment {
+    pub primary: BytesN<32>,
+    pub backup: BytesN<32>,
+    pub assigned_at: u32,
+    pub window: u32,
+}
+
+/// Consumer contract notified through `on_batch_attested` when a batch is attested
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub app_id: Symbol,
+    pub consumer: Address,
+}
+
+/// Attestation submitted for a batch
+/// Stays PROPOSED until a challenge against it is resolved
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestationRecord {
+    pub tee_pubkey: BytesN<32>,
+    pub state_root: BytesN<32>,
+    pub status: AttestationStatus,
+    pub attested_at: u64,
+}
+
+/// Result published with a batch attestation
+/// `outputs_hash` commits to all batch outputs; when `public_output` is set
+/// it is the SHA-256 of that output
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchOutput {
+    pub public_output: Option<Bytes>,
+    pub outputs_hash: BytesN<32>,
+}
+
+/// Sealed enclave state published after a batch so another TEE holding the
+/// network key can resume from it
+/// Large blobs are kept off-chain and only their hash is stored
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateCheckpoint {
+    pub tee_pubkey: BytesN<32>,
+    pub blob_hash: BytesN<32>,
+    pub blob: Option<Bytes>,
+    pub published_at: u64,
+}
+
+/// How TEE operators are slashed through the Staking contract when a
+/// challenge against their attestation is upheld
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlashingConfig {
+    pub staking_contract: Address,
+    pub treasury: Address,
+    pub slash_amount: i128,
+    pub challenger_share_bps: u32,
+}
+
+#[contracttype]
+pub enum DataKey {
+    SubmitterInputs(u64, Address),
+    SubmitterNonce(Address),
+    EncryptionKey(u64),
+    BatchAssignment(u64),
+    Attestation(u64),
+    TeeOperator(BytesN<32>),
+    BatchReverted(u64),
+    BatchOutput(u64),
+    StateCheckpoint(u64),
+    AllowedSubmitter(Address),
+    InputReference(u64, u32),
 }
 
 #[contracterror]
@@ -32,10 +172,230 @@ pub enum Error {
     TeeNotRegistered = 4,
     BatchAlreadyAttested = 5,
     Unauthorized = 6,
+    InputNotFound = 7,
+    BatchClosed = 8,
+    InputAlreadyWithdrawn = 9,
+    BatchFull = 10,
+    SubmitterLimitReached = 11,
+    AlreadyInitialized = 12,
+    NotInitialized = 13,
+    InvalidNonce = 14,
+    InvalidEpoch = 15,
+    EpochNotFound = 16,
+    EpochExpired = 17,
+    TeeNotAssigned = 18,
+    TooManySubscribers = 19,
+    AttestationNotProposed = 20,
+    SlashingNotConfigured = 21,
+    TeeOperatorNotSet = 22,
+    BatchNotAttested = 23,
+    PublicOutputTooLarge = 24,
+    InvalidOutputsHash = 25,
+    StateBlobTooLarge = 26,
+    InvalidStateBlobHash = 27,
+    CheckpointAlreadyPublished = 28,
+    SubmitterNotAllowed = 29,
+    DataAvailabilityModeEnabled = 30,
+    DataAvailabilityModeDisabled = 31,
+    NotKeyPublisher = 32,
+    UnsupportedLocator = 33,
+    InputReferenceTooLarge = 34,
+    BatchNotSealed = 35,
+    InvalidSlashingConfig = 36,
+}
+
+fn require_admin(env: &Env) -> Address {
+    let admin: Address = match env.storage().instance().get(&ADMIN) {
+        Some(admin) => admin,
+        None => panic_with_error!(env, Error::NotInitialized),
+    };
+    admin.require_auth();
+    admin
+}
+
+/// Message a TEE signs with its attestation key to publish an encryption key:
+/// epoch (8 bytes BE) || public_key (32 bytes) || expires_at (8 bytes BE)
+fn encryption_key_message(
+    env: &Env,
+    epoch: u64,
+    public_key: &BytesN<32>,
+    expires_at: u64,
+) -> Bytes {
+    let mut message = Bytes::from_array(env, &epoch.to_be_bytes());
+    message.extend_from_array(&public_key.to_array());
+    message.extend_from_array(&expires_at.to_be_bytes());
+    message
+}
+
+/// Message a TEE signs with its attestation key to attest a batch:
+/// batch_id (8 bytes BE) || state_root (32 bytes) || outputs_hash (32 bytes)
+fn attestation_message(
+    env: &Env,
+    batch_id: u64,
+    state_root: &BytesN<32>,
+    outputs_hash: &BytesN<32>,
+) -> Bytes {
+    let mut message = Bytes::from_array(env, &batch_id.to_be_bytes());
+    message.extend_from_array(&state_root.to_array());
+    message.extend_from_array(&outputs_hash.to_array());
+    message
+}
+
+/// Message a TEE signs with its attestation key to publish a state checkpoint:
+/// batch_id (8 bytes BE) || blob_hash (32 bytes)
+fn state_checkpoint_message(env: &Env, batch_id: u64, blob_hash: &BytesN<32>) -> Bytes {
+    let mut message = Bytes::from_array(env, &batch_id.to_be_bytes());
+    message.extend_from_array(&blob_hash.to_array());
+    message
 }
 
 #[contractimpl]
 impl OnchainCommitment {
+    /// Set the contract admin, can only be called once
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().instance().has(&ADMIN) || admin::admin_renounced(&env) {
+            panic_with_error!(&env, Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().extend_ttl(100, 100);
+    }
+
+    /// Get the contract admin
+    pub fn admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ADMIN)
+    }
+
+    /// Get the admin transfer waiting to be accepted
+    pub fn pending_admin(env: Env) -> Option<PendingAdmin> {
+        admin::pending_admin(&env)
+    }
+
+    /// Propose a new admin, who must accept before the proposal expires
+    /// Only callable by the admin
+    pub fn propose_admin(env: Env, new_admin: Address) -> PendingAdmin {
+        admin::propose_admin(&env, &ADMIN, new_admin)
+    }
+
+    /// Accept a pending admin transfer
+    /// Only callable by the proposed admin
+    pub fn accept_admin(env: Env) -> Address {
+        admin::accept_admin(&env, &ADMIN)
+    }
+
+    /// Give up the admin role permanently
+    /// Only callable by the admin
+    pub fn renounce_admin(env: Env) {
+        admin::renounce_admin(&env, &ADMIN)
+    }
+
+    /// Get the input caps applied to every batch
+    pub fn batch_limits(env: Env) -> BatchLimits {
+        env.storage()
+            .instance()
+            .get(&BATCH_LIMITS)
+            .unwrap_or(BatchLimits {
+                max_inputs_per_batch: 0,
+                max_inputs_per_submitter: 0,
+            })
+    }
+
+    /// Configure the input caps applied to every batch, 0 disables a cap
+    /// Only callable by the admin
+    pub fn set_batch_limits(env: Env, max_inputs_per_batch: u32, max_inputs_per_submitter: u32) {
+        require_admin(&env);
+
+        let limits = BatchLimits {
+            max_inputs_per_batch,
+            max_inputs_per_submitter,
+        };
+        env.storage().instance().set(&BATCH_LIMITS, &limits);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let max_batch_val: Val = max_inputs_per_batch.into_val(&env);
+        let max_submitter_val: Val = max_inputs_per_submitter.into_val(&env);
+        env.events().publish(
+            ("BATCH_LIMITS_UPDATED",),
+            (max_batch_val, max_submitter_val),
+        );
+    }
+
+    /// Get the number of inputs a submitter has added to the open batch
+    /// Counters are cleared when the batch is sealed
+    pub fn submitter_input_count(env: Env, batch_id: u64, submitter: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SubmitterInputs(batch_id, submitter))
+            .unwrap_or(0)
+    }
+
+    /// Check if only allowlisted submitters may submit inputs
+    pub fn allowlist_enabled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ALLOWLIST_ENABLED)
+            .unwrap_or(false)
+    }
+
+    /// Switch the submitter allowlist on or off
+    /// Only callable by the admin
+    pub fn set_allowlist_enabled(env: Env, enabled: bool) {
+        require_admin(&env);
+
+        env.storage().instance().set(&ALLOWLIST_ENABLED, &enabled);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let enabled_val: Val = enabled.into_val(&env);
+        env.events().publish(("ALLOWLIST_UPDATED",), (enabled_val,));
+    }
+
+    /// Check if a submitter is on the allowlist
+    pub fn is_submitter_allowed(env: Env, submitter: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AllowedSubmitter(submitter))
+            .unwrap_or(false)
+    }
+
+    /// Add a submitter to the allowlist
+    /// Only callable by the admin
+    pub fn add_submitter(env: Env, submitter: Address) -> bool {
+        require_admin(&env);
+
+        let key = DataKey::AllowedSubmitter(submitter.clone());
+        if env.storage().persistent().has(&key) {
+            return false;
+        }
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        // Emit event
+        let submitter_val: Val = submitter.into_val(&env);
+        env.events().publish(("SUBMITTER_ADDED",), (submitter_val,));
+
+        true
+    }
+
+    /// Remove a submitter from the allowlist
+    /// Only callable by the admin
+    pub fn remove_submitter(env: Env, submitter: Address) -> bool {
+        require_admin(&env);
+
+        let key = DataKey::AllowedSubmitter(submitter.clone());
+        if !env.storage().persistent().has(&key) {
+            return false;
+        }
+        env.storage().persistent().remove(&key);
+
+        // Emit event
+        let submitter_val: Val = submitter.into_val(&env);
+        env.events().publish(("SUBMITTER_REMOVED",), (submitter_val,));
+
+        true
+    }
+
     /// Get the current batch ID
     pub fn current_batch_id(env: Env) -> u64 {
         env.storage()
@@ -49,6 +409,39 @@ impl OnchainCommitment {
         env.storage().instance().get(&CURRENT_STATE_ROOT)
     }
 
+    /// Get the state root attested for a batch
+    /// Returns None if the batch has no attestation or its attestation failed
+    pub fn state_root_at(env: Env, batch_id: u64) -> Option<BytesN<32>> {
+        match Self::attestation(env, batch_id) {
+            Some(record) if record.status != AttestationStatus::FAILED => Some(record.state_root),
+            _ => None,
+        }
+    }
+
+    /// Get the public output and outputs hash attested for a batch
+    pub fn batch_output(env: Env, batch_id: u64) -> Option<BatchOutput> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BatchOutput(batch_id))
+    }
+
+    /// Get the state checkpoint published for a batch
+    pub fn state_checkpoint(env: Env, batch_id: u64) -> Option<StateCheckpoint> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StateCheckpoint(batch_id))
+    }
+
+    /// Get the hash of the sealed state blob published for a batch
+    pub fn state_blob_hash(env: Env, batch_id: u64) -> Option<BytesN<32>> {
+        Self::state_checkpoint(env, batch_id).map(|checkpoint| checkpoint.blob_hash)
+    }
+
+    /// Get the highest attested batch ID
+    pub fn latest_finalized_batch(env: Env) -> Option<u64> {
+        env.storage().instance().get(&LATEST_FINALIZED_BATCH)
+    }
+
     /// Get all input commitments for a specific batch
     pub fn batch_inputs(env: Env, batch_id: u64) -> Vec<InputCommitment> {
         let batch_storage: Map<u64, Vec<InputCommitment>> = env
@@ -62,7 +455,10 @@ impl OnchainCommitment {
 
     /// Register a TEE with its public key
     /// Returns true if registration was successful, false if already registered
+    /// Only callable by the admin
     pub fn register_tee(env: Env, tee_pubkey: BytesN<32>) -> bool {
+        require_admin(&env);
+
         let mut tees: Map<BytesN<32>, bool> = env
             .storage()
             .instance()
@@ -97,28 +493,315 @@ impl OnchainCommitment {
         attested_batches.get(batch_id).unwrap_or(false)
     }
 
+    /// Get the latest published encryption key epoch, 0 if none
+    pub fn current_epoch(env: Env) -> u64 {
+        env.storage().instance().get(&CURRENT_EPOCH).unwrap_or(0)
+    }
+
+    /// Get the encryption key of the latest epoch
+    pub fn current_encryption_key(env: Env) -> Option<EncryptionKey> {
+        let epoch = Self::current_epoch(env.clone());
+        Self::encryption_key(env, epoch)
+    }
+
+    /// Get the encryption key published for an epoch
+    pub fn encryption_key(env: Env, epoch: u64) -> Option<EncryptionKey> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EncryptionKey(epoch))
+    }
+
+    /// Get the TEE allowed to publish encryption keys
+    pub fn key_publisher(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&KEY_PUBLISHER)
+    }
+
+    /// Designate the registered TEE that publishes the encryption keys of
+    /// the next epochs
+    /// Only callable by the admin
+    pub fn set_key_publisher(env: Env, tee_pubkey: BytesN<32>) {
+        require_admin(&env);
+
+        if !Self::is_tee_registered(env.clone(), tee_pubkey.clone()) {
+            panic_with_error!(&env, Error::TeeNotRegistered);
+        }
+
+        env.storage().instance().set(&KEY_PUBLISHER, &tee_pubkey);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
+        env.events().publish(("KEY_PUBLISHER_UPDATED",), (tee_pubkey_val,));
+    }
+
+    /// Publish the X25519 encryption key for the next epoch
+    /// Only callable by the key publisher the admin designated, the signature
+    /// must be made by tee_pubkey over epoch || public_key || expires_at
+    pub fn publish_encryption_key(
+        env: Env,
+        tee_pubkey: BytesN<32>,
+        epoch: u64,
+        public_key: BytesN<32>,
+        expires_at: u64,
+        signature: BytesN<64>,
+    ) {
+        // Verify TEE is registered and designated to publish keys
+        if !Self::is_tee_registered(env.clone(), tee_pubkey.clone()) {
+            panic_with_error!(&env, Error::TeeNotRegistered);
+        }
+        if Self::key_publisher(env.clone()) != Some(tee_pubkey.clone()) {
+            panic_with_error!(&env, Error::NotKeyPublisher);
+        }
+
+        // Epochs are published in order, starting at 1
+        if epoch != Self::current_epoch(env.clone()) + 1 || expires_at <= env.ledger().timestamp() {
+            panic_with_error!(&env, Error::InvalidEpoch);
+        }
+
+        // Verify the key was signed by the TEE attestation key
+        let message = encryption_key_message(&env, epoch, &public_key, expires_at);
+        env.crypto().ed25519_verify(&tee_pubkey, &message, &signature);
+
+        let encryption_key = EncryptionKey {
+            epoch,
+            public_key: public_key.clone(),
+            tee_pubkey: tee_pubkey.clone(),
+            published_at: env.ledger().timestamp(),
+            expires_at,
+        };
+
+        let key = DataKey::EncryptionKey(epoch);
+        env.storage().persistent().set(&key, &encryption_key);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+        env.storage().instance().set(&CURRENT_EPOCH, &epoch);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let epoch_val: Val = epoch.into_val(&env);
+        let public_key_val: Val = public_key.into_val(&env);
+        let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
+        let expires_at_val: Val = expires_at.into_val(&env);
+        env.events().publish(
+            ("ENCRYPTION_KEY_PUBLISHED",),
+            (epoch_val, public_key_val, tee_pubkey_val, expires_at_val),
+        );
+    }
+
+    /// Get the next nonce expected from a submitter
+    pub fn submitter_nonce(env: Env, submitter: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SubmitterNonce(submitter))
+            .unwrap_or(0)
+    }
+
     /// Submit encrypted input to the current batch
+    /// The nonce must equal `submitter_nonce(submitter)`, which rejects replays
+    /// and gives every submitter's inputs a canonical order
+    /// The epoch names the encryption key the data was encrypted to and must not be expired
+    /// Requires the submitter's authorization
     pub fn submit_encrypted_input(
         env: Env,
-        submitter: String,
+        submitter: Address,
         encrypted_data: String,
+        nonce: u64,
+        epoch: u64,
     ) -> u64 {
         // Validate encrypted data is not empty
-        if encrypted_data.len() == 0 {
+        if encrypted_data.is_empty() {
             panic_with_error!(&env, Error::InvalidEncryptedData);
         }
 
-        // Get current batch ID
-        let current_batch_id = Self::current_batch_id(env.clone());
+        // Ciphertexts stay off-chain in data-availability mode
+        if Self::data_availability_mode(env.clone()) {
+            panic_with_error!(&env, Error::DataAvailabilityModeEnabled);
+        }
 
-        // Create input commitment
-        let input_commitment = InputCommitment {
-            encrypted_data: encrypted_data.clone(),
-            submitter: submitter.clone(),
-            timestamp: env.ledger().timestamp(),
+        let (current_batch_id, index) = Self::append_inputs(
+            &env,
+            &submitter,
+            nonce,
+            epoch,
+            vec![&env, encrypted_data],
+        );
+
+        // Emit event
+        let batch_id_val: Val = current_batch_id.into_val(&env);
+        let submitter_val: Val = submitter.into_val(&env);
+        let index_val: Val = index.into_val(&env);
+        let nonce_val: Val = nonce.into_val(&env);
+        env.events().publish(
+            ("ENCRYPTED_INPUT_SUBMITTED",),
+            (batch_id_val, submitter_val, index_val, nonce_val),
+        );
+
+        current_batch_id
+    }
+
+    /// Check if inputs are submitted as references to off-chain ciphertexts
+    pub fn data_availability_mode(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DATA_AVAILABILITY_MODE)
+            .unwrap_or(false)
+    }
+
+    /// Switch data-availability mode on or off
+    /// While it is on only `submit_input_reference` accepts inputs
+    /// Only callable by the admin
+    pub fn set_data_availability_mode(env: Env, enabled: bool) {
+        require_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&DATA_AVAILABILITY_MODE, &enabled);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let enabled_val: Val = enabled.into_val(&env);
+        env.events().publish(("DATA_AVAILABILITY_MODE_UPDATED",), (enabled_val,));
+    }
+
+    /// Get the off-chain ciphertext reference of an input
+    pub fn input_reference(env: Env, batch_id: u64, index: u32) -> Option<DataReference> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::InputReference(batch_id, index))
+    }
+
+    /// Submit an input whose ciphertext is stored off-chain
+    /// Only the SHA-256 of the ciphertext and its length are kept; TEEs fetch
+    /// the ciphertext from the content-addressed store by its hash and check
+    /// it against both, so the locator must be empty
+    /// The ciphertext may be at most MAX_INPUT_REFERENCE_SIZE bytes
+    /// Same nonce, epoch and authorization rules as `submit_encrypted_input`
+    pub fn submit_input_reference(
+        env: Env,
+        submitter: Address,
+        data_hash: BytesN<32>,
+        length: u32,
+        locator: String,
+        nonce: u64,
+        epoch: u64,
+    ) -> u64 {
+        if length == 0 {
+            panic_with_error!(&env, Error::InvalidEncryptedData);
+        }
+        if length > MAX_INPUT_REFERENCE_SIZE {
+            panic_with_error!(&env, Error::InputReferenceTooLarge);
+        }
+        if !locator.is_empty() {
+            panic_with_error!(&env, Error::UnsupportedLocator);
+        }
+
+        if !Self::data_availability_mode(env.clone()) {
+            panic_with_error!(&env, Error::DataAvailabilityModeDisabled);
+        }
+
+        // The commitment itself carries no ciphertext
+        let (current_batch_id, index) = Self::append_inputs(
+            &env,
+            &submitter,
+            nonce,
+            epoch,
+            vec![&env, String::from_str(&env, "")],
+        );
+
+        let data_ref = DataReference {
+            data_hash: data_hash.clone(),
+            length,
+            locator: locator.clone(),
         };
+        let ref_key = DataKey::InputReference(current_batch_id, index);
+        env.storage().persistent().set(&ref_key, &data_ref);
+        env.storage().persistent().extend_ttl(&ref_key, 100, 100);
+
+        // Emit event
+        let batch_id_val: Val = current_batch_id.into_val(&env);
+        let submitter_val: Val = submitter.into_val(&env);
+        let index_val: Val = index.into_val(&env);
+        let nonce_val: Val = nonce.into_val(&env);
+        let data_hash_val: Val = data_hash.into_val(&env);
+        let length_val: Val = length.into_val(&env);
+        let locator_val: Val = locator.into_val(&env);
+        env.events().publish(
+            ("ENCRYPTED_INPUT_SUBMITTED",),
+            (
+                batch_id_val,
+                submitter_val,
+                index_val,
+                nonce_val,
+                data_hash_val,
+                length_val,
+                locator_val,
+            ),
+        );
+
+        current_batch_id
+    }
+
+    /// Submit several encrypted inputs to the current batch in one call
+    /// The inputs take consecutive nonces starting at `nonce` and are added
+    /// all together or not at all, within the batch caps
+    /// Requires the submitter's authorization
+    /// Returns the indices of the inputs in the current batch
+    ///
+    /// The inputs are `String`s rather than `Bytes`, as in
+    /// `submit_encrypted_input`, since that is how `InputCommitment` stores
+    /// them; `nonce` and `epoch` are checked as on every other submit path; and
+    /// the indices are `u32` rather than `u64`, the index type of
+    /// `withdraw_input` and of the batch's `Vec`
+    pub fn submit_encrypted_inputs(
+        env: Env,
+        submitter: Address,
+        encrypted_inputs: Vec<String>,
+        nonce: u64,
+        epoch: u64,
+    ) -> Vec<u32> {
+        if encrypted_inputs.is_empty()
+            || encrypted_inputs.iter().any(|encrypted_data| encrypted_data.is_empty())
+        {
+            panic_with_error!(&env, Error::InvalidEncryptedData);
+        }
+
+        if Self::data_availability_mode(env.clone()) {
+            panic_with_error!(&env, Error::DataAvailabilityModeEnabled);
+        }
+
+        let count = encrypted_inputs.len();
+        let (current_batch_id, first_index) =
+            Self::append_inputs(&env, &submitter, nonce, epoch, encrypted_inputs);
+
+        // Emit event
+        let batch_id_val: Val = current_batch_id.into_val(&env);
+        let submitter_val: Val = submitter.into_val(&env);
+        let first_index_val: Val = first_index.into_val(&env);
+        let nonce_val: Val = nonce.into_val(&env);
+        let count_val: Val = count.into_val(&env);
+        env.events().publish(
+            ("ENCRYPTED_INPUTS_SUBMITTED",),
+            (batch_id_val, submitter_val, first_index_val, nonce_val, count_val),
+        );
+
+        let mut indices = Vec::<u32>::new(&env);
+        for index in first_index..first_index + count {
+            indices.push_back(index);
+        }
+        indices
+    }
+
+    /// Withdraw a previously submitted input while its batch is still open
+    /// Only callable by the original submitter; the commitment is kept as a
+    /// tombstone so that the indices of the other inputs do not shift
+    pub fn withdraw_input(env: Env, batch_id: u64, index: u32) {
+        // Only the current batch accepts changes, and never once attested
+        if batch_id != Self::current_batch_id(env.clone()) {
+            panic_with_error!(&env, Error::BatchClosed);
+        }
+        if env.storage().persistent().has(&DataKey::Attestation(batch_id)) {
+            panic_with_error!(&env, Error::BatchAlreadyAttested);
+        }
 
-        // Get existing batch inputs
         let mut batch_storage: Map<u64, Vec<InputCommitment>> = env
             .storage()
             .instance()
@@ -126,32 +809,78 @@ impl OnchainCommitment {
             .unwrap_or_else(|| Map::new(&env));
 
         let mut inputs = batch_storage
-            .get(current_batch_id)
+            .get(batch_id)
             .unwrap_or_else(|| Vec::new(&env));
-        inputs.push_back(input_commitment.clone());
-        batch_storage.set(current_batch_id, inputs);
+        let mut input_commitment = match inputs.get(index) {
+            Some(input_commitment) => input_commitment,
+            None => panic_with_error!(&env, Error::InputNotFound),
+        };
+
+        if input_commitment.withdrawn {
+            panic_with_error!(&env, Error::InputAlreadyWithdrawn);
+        }
+
+        // Verify the caller is the original submitter
+        input_commitment.submitter.require_auth();
+
+        // Tombstone the commitment
+        input_commitment.encrypted_data = String::from_str(&env, "");
+        input_commitment.withdrawn = true;
+        inputs.set(index, input_commitment.clone());
+        batch_storage.set(batch_id, inputs);
 
         // Persist storage
         env.storage().instance().set(&BATCH_INPUTS, &batch_storage);
         env.storage().instance().extend_ttl(100, 100);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::InputReference(batch_id, index));
 
         // Emit event
-        let batch_id_val: Val = current_batch_id.into_val(&env);
-        let submitter_val: Val = submitter.into_val(&env);
+        let batch_id_val: Val = batch_id.into_val(&env);
+        let index_val: Val = index.into_val(&env);
+        let submitter_val: Val = input_commitment.submitter.into_val(&env);
         env.events().publish(
-            ("ENCRYPTED_INPUT_SUBMITTED",),
-            (batch_id_val, submitter_val),
+            ("INPUT_WITHDRAWN",),
+            (batch_id_val, index_val, submitter_val),
         );
+    }
 
-        current_batch_id
+    /// Get the number of ledgers a sealed batch is reserved for its primary TEE
+    pub fn assignment_window(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ASSIGNMENT_WINDOW)
+            .unwrap_or(DEFAULT_ASSIGNMENT_WINDOW)
+    }
+
+    /// Set the number of ledgers a sealed batch is reserved for its primary TEE
+    /// Only callable by the admin
+    pub fn set_assignment_window(env: Env, window: u32) {
+        require_admin(&env);
+
+        env.storage().instance().set(&ASSIGNMENT_WINDOW, &window);
+        env.storage().instance().extend_ttl(100, 100);
+    }
+
+    /// Get the TEEs assigned to attest a batch
+    pub fn batch_assignment(env: Env, batch_id: u64) -> Option<BatchAssignment> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BatchAssignment(batch_id))
     }
 
     /// Create a new batch (increment batch ID)
+    /// Seals the current batch, clears its per-submitter counters and assigns
+    /// its primary and backup TEEs round-robin over the registered TEEs
     /// Only callable by authorized addresses (TODO: add access control)
     pub fn create_new_batch(env: Env) -> u64 {
         let current_batch_id = Self::current_batch_id(env.clone());
         let new_batch_id = current_batch_id + 1;
 
+        Self::clear_submitter_counts(&env, current_batch_id);
+        Self::assign_batch(&env, current_batch_id);
+
         env.storage()
             .instance()
             .set(&CURRENT_BATCH_ID, &new_batch_id);
@@ -175,13 +904,275 @@ impl OnchainCommitment {
         env.events().publish(("STATE_ROOT_UPDATED",), (state_root_val,));
     }
 
-    /// Submit attestation for a batch
-    /// Only callable by registered TEEs
+    /// Get the consumer contracts notified when a batch is attested
+    pub fn subscribers(env: Env) -> Vec<Subscription> {
+        env.storage()
+            .instance()
+            .get(&SUBSCRIBERS)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Subscribe a consumer contract to batch attestations
+    /// The consumer must implement `on_batch_attested(batch_id, state_root, outputs_hash)`
+    /// Every callback runs inside `submit_attestation`, so subscriptions are
+    /// approved by the admin and require the consumer's authorization
+    /// Returns true if the subscription was added, false if already subscribed
+    pub fn subscribe(env: Env, app_id: Symbol, consumer_contract: Address) -> bool {
+        require_admin(&env);
+        consumer_contract.require_auth();
+
+        let mut subscribers = Self::subscribers(env.clone());
+        if subscribers
+            .iter()
+            .any(|subscription| subscription.consumer == consumer_contract)
+        {
+            return false;
+        }
+
+        if subscribers.len() >= MAX_SUBSCRIBERS {
+            panic_with_error!(&env, Error::TooManySubscribers);
+        }
+
+        subscribers.push_back(Subscription {
+            app_id: app_id.clone(),
+            consumer: consumer_contract.clone(),
+        });
+        env.storage().instance().set(&SUBSCRIBERS, &subscribers);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let app_id_val: Val = app_id.into_val(&env);
+        let consumer_val: Val = consumer_contract.into_val(&env);
+        env.events()
+            .publish(("CONSUMER_SUBSCRIBED",), (app_id_val, consumer_val));
+
+        true
+    }
+
+    /// Remove a consumer contract from the batch attestation subscribers
+    /// Returns true if the subscription was removed, false if not subscribed
+    pub fn unsubscribe(env: Env, consumer_contract: Address) -> bool {
+        consumer_contract.require_auth();
+
+        Self::remove_subscription(&env, &consumer_contract)
+    }
+
+    /// Evict a consumer contract, e.g. one whose callback makes attestations fail
+    /// Returns true if the subscription was removed, false if not subscribed
+    /// Only callable by the admin
+    pub fn remove_subscriber(env: Env, consumer_contract: Address) -> bool {
+        require_admin(&env);
+
+        Self::remove_subscription(&env, &consumer_contract)
+    }
+
+    /// Get the slashing configuration
+    pub fn slashing_config(env: Env) -> Option<SlashingConfig> {
+        env.storage().instance().get(&SLASHING_CONFIG)
+    }
+
+    /// Configure slashing through the Staking contract
+    /// This contract must be an authorized slasher in the Staking contract
+    /// Only callable by the admin
+    pub fn set_slashing_config(
+        env: Env,
+        staking_contract: Address,
+        treasury: Address,
+        slash_amount: i128,
+        challenger_share_bps: u32,
+    ) {
+        require_admin(&env);
+
+        if slash_amount < 0 || challenger_share_bps > 10_000 {
+            panic_with_error!(&env, Error::InvalidSlashingConfig);
+        }
+
+        let config = SlashingConfig {
+            staking_contract,
+            treasury,
+            slash_amount,
+            challenger_share_bps,
+        };
+        env.storage().instance().set(&SLASHING_CONFIG, &config);
+        env.storage().instance().extend_ttl(100, 100);
+    }
+
+    /// Get the operator whose stake backs a TEE
+    pub fn tee_operator(env: Env, tee_pubkey: BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TeeOperator(tee_pubkey))
+    }
+
+    /// Set the operator whose stake backs a TEE
+    /// Only callable by the admin
+    pub fn set_tee_operator(env: Env, tee_pubkey: BytesN<32>, operator: Address) {
+        require_admin(&env);
+
+        if !Self::is_tee_registered(env.clone(), tee_pubkey.clone()) {
+            panic_with_error!(&env, Error::TeeNotRegistered);
+        }
+
+        let key = DataKey::TeeOperator(tee_pubkey);
+        env.storage().persistent().set(&key, &operator);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+    }
+
+    /// Get the attestation submitted for a batch
+    pub fn attestation(env: Env, batch_id: u64) -> Option<AttestationRecord> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Attestation(batch_id))
+    }
+
+    /// Resolve a challenge against a PROPOSED attestation
+    /// If the challenge is upheld the attestation is marked FAILED and the
+    /// state is rolled back to the latest batch attested before it, so the
+    /// batch and the ones after it can be attested again; the TEE operator is
+    /// then slashed when possible. Otherwise the attestation is marked SUCCESS
+    /// Only callable by the admin
+    pub fn resolve_challenge(env: Env, batch_id: u64, challenger: Address, upheld: bool) {
+        require_admin(&env);
+
+        let key = DataKey::Attestation(batch_id);
+        let mut record = match Self::attestation(env.clone(), batch_id) {
+            Some(record) => record,
+            None => panic_with_error!(&env, Error::BatchNotFound),
+        };
+        if record.status != AttestationStatus::PROPOSED {
+            panic_with_error!(&env, Error::AttestationNotProposed);
+        }
+
+        if !upheld {
+            record.status = AttestationStatus::SUCCESS;
+            env.storage().persistent().set(&key, &record);
+            env.storage().persistent().extend_ttl(&key, 100, 100);
+
+            // Emit event
+            let batch_id_val: Val = batch_id.into_val(&env);
+            let challenger_val: Val = challenger.into_val(&env);
+            env.events()
+                .publish(("CHALLENGE_REJECTED",), (batch_id_val, challenger_val));
+            return;
+        }
+
+        record.status = AttestationStatus::FAILED;
+        env.storage().persistent().set(&key, &record);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        // Discard the failed batch and everything built on its state root,
+        // keeping the FAILED record until the batch is attested again
+        let mut attested_batches: Map<u64, bool> = env
+            .storage()
+            .instance()
+            .get(&BATCH_ATTESTED)
+            .unwrap_or_else(|| Map::new(&env));
+        attested_batches.remove(batch_id);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::BatchOutput(batch_id));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::StateCheckpoint(batch_id));
+        let reverted_batches =
+            Self::discard_attestations_after(&env, &mut attested_batches, batch_id);
+        env.storage().instance().set(&BATCH_ATTESTED, &attested_batches);
+
+        // Roll the head back to the latest remaining attested batch
+        let head = attested_batches.keys().last();
+        let head_root = head.and_then(|head| Self::state_root_at(env.clone(), head));
+        match (head, &head_root) {
+            (Some(head), Some(head_root)) => {
+                env.storage().instance().set(&CURRENT_STATE_ROOT, head_root);
+                env.storage().instance().set(&LATEST_FINALIZED_BATCH, &head);
+            }
+            _ => {
+                env.storage().instance().remove(&CURRENT_STATE_ROOT);
+                env.storage().instance().remove(&LATEST_FINALIZED_BATCH);
+            }
+        }
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let batch_id_val: Val = batch_id.into_val(&env);
+        let head_root_val: Val = head_root.into_val(&env);
+        let reverted_batches_val: Val = reverted_batches.into_val(&env);
+        env.events().publish(
+            ("CHALLENGE_UPHELD",),
+            (batch_id_val, head_root_val, reverted_batches_val),
+        );
+
+        Self::slash_operator(&env, batch_id, &record.tee_pubkey, &challenger);
+    }
+
+    /// Check if a batch attestation has been reverted by `revert_to_batch` and
+    /// not attested again since
+    pub fn batch_reverted(env: Env, batch_id: u64) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BatchReverted(batch_id))
+            .unwrap_or(false)
+    }
+
+    /// Roll the state back to the root attested for a batch
+    /// Attestations of all later batches are discarded and those batches can
+    /// be attested again
+    /// Only callable by the admin
+    pub fn revert_to_batch(env: Env, batch_id: u64) {
+        require_admin(&env);
+
+        // The target must hold a valid attestation
+        let record = match Self::attestation(env.clone(), batch_id) {
+            Some(record) if record.status != AttestationStatus::FAILED => record,
+            _ => panic_with_error!(&env, Error::BatchNotAttested),
+        };
+
+        let previous_root = Self::current_state_root(env.clone());
+        let mut attested_batches: Map<u64, bool> = env
+            .storage()
+            .instance()
+            .get(&BATCH_ATTESTED)
+            .unwrap_or_else(|| Map::new(&env));
+
+        // Discard the attestations of later batches
+        let reverted_batches =
+            Self::discard_attestations_after(&env, &mut attested_batches, batch_id);
+
+        env.storage().instance().set(&BATCH_ATTESTED, &attested_batches);
+        env.storage()
+            .instance()
+            .set(&CURRENT_STATE_ROOT, &record.state_root);
+        env.storage()
+            .instance()
+            .set(&LATEST_FINALIZED_BATCH, &batch_id);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let batch_id_val: Val = batch_id.into_val(&env);
+        let state_root_val: Val = record.state_root.into_val(&env);
+        let previous_root_val: Val = previous_root.into_val(&env);
+        let reverted_batches_val: Val = reverted_batches.into_val(&env);
+        env.events().publish(
+            ("STATE_REVERTED",),
+            (batch_id_val, state_root_val, previous_root_val, reverted_batches_val),
+        );
+    }
+
+    /// Submit attestation for a sealed batch
+    /// Only callable by registered TEEs, and by the assigned TEEs while the
+    /// batch assignment windows are open
+    /// The optional public output (e.g. a vote tally) is capped in size and
+    /// must hash to outputs_hash
+    /// The signature must be made by tee_pubkey over
+    /// batch_id || state_root || outputs_hash
     pub fn submit_attestation(
         env: Env,
         tee_pubkey: BytesN<32>,
         batch_id: u64,
         state_root: BytesN<32>,
+        public_output: Option<Bytes>,
+        outputs_hash: BytesN<32>,
+        signature: BytesN<64>,
     ) {
         // Verify TEE is registered
         let tees: Map<BytesN<32>, bool> = env
@@ -194,11 +1185,42 @@ impl OnchainCommitment {
             panic_with_error!(&env, Error::TeeNotRegistered);
         }
 
-        // Check if batch already attested
+        // Verify the attestation was signed by the TEE attestation key
+        let message = attestation_message(&env, batch_id, &state_root, &outputs_hash);
+        env.crypto().ed25519_verify(&tee_pubkey, &message, &signature);
+
+        // Only sealed batches can be attested, and only once
+        if batch_id >= Self::current_batch_id(env.clone()) {
+            panic_with_error!(&env, Error::BatchNotSealed);
+        }
         if Self::batch_attested(env.clone(), batch_id) {
             panic_with_error!(&env, Error::BatchAlreadyAttested);
         }
 
+        // Check the TEE holds the attestation rights for the batch
+        if let Some(assignment) = Self::batch_assignment(env.clone(), batch_id) {
+            let ledger = env.ledger().sequence();
+            let backup_from = assignment.assigned_at.saturating_add(assignment.window);
+            let open_from = backup_from.saturating_add(assignment.window);
+            let allowed = tee_pubkey == assignment.primary
+                || (tee_pubkey == assignment.backup && ledger >= backup_from)
+                || ledger >= open_from;
+            if !allowed {
+                panic_with_error!(&env, Error::TeeNotAssigned);
+            }
+        }
+
+        // Validate the public output against the outputs hash
+        if let Some(output) = &public_output {
+            if output.len() > MAX_PUBLIC_OUTPUT_SIZE {
+                panic_with_error!(&env, Error::PublicOutputTooLarge);
+            }
+            let output_hash: BytesN<32> = env.crypto().sha256(output).into();
+            if output_hash != outputs_hash {
+                panic_with_error!(&env, Error::InvalidOutputsHash);
+            }
+        }
+
         // Mark batch as attested
         let mut attested_batches: Map<u64, bool> = env
             .storage()
@@ -207,9 +1229,37 @@ impl OnchainCommitment {
             .unwrap_or_else(|| Map::new(&env));
         attested_batches.set(batch_id, true);
         env.storage().instance().set(&BATCH_ATTESTED, &attested_batches);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::BatchReverted(batch_id));
 
-        // Update state root
-        env.storage().instance().set(&CURRENT_STATE_ROOT, &state_root);
+        // Record the attestation so it can be challenged
+        let record = AttestationRecord {
+            tee_pubkey: tee_pubkey.clone(),
+            state_root: state_root.clone(),
+            status: AttestationStatus::PROPOSED,
+            attested_at: env.ledger().timestamp(),
+        };
+        let record_key = DataKey::Attestation(batch_id);
+        env.storage().persistent().set(&record_key, &record);
+        env.storage().persistent().extend_ttl(&record_key, 100, 100);
+
+        // Store the batch output
+        let output = BatchOutput {
+            public_output,
+            outputs_hash: outputs_hash.clone(),
+        };
+        let output_key = DataKey::BatchOutput(batch_id);
+        env.storage().persistent().set(&output_key, &output);
+        env.storage().persistent().extend_ttl(&output_key, 100, 100);
+
+        // Advance the head, unless a later batch was already attested
+        if Self::latest_finalized_batch(env.clone()).is_none_or(|latest| batch_id > latest) {
+            env.storage().instance().set(&CURRENT_STATE_ROOT, &state_root);
+            env.storage()
+                .instance()
+                .set(&LATEST_FINALIZED_BATCH, &batch_id);
+        }
 
         env.storage().instance().extend_ttl(100, 100);
 
@@ -217,9 +1267,72 @@ impl OnchainCommitment {
         let batch_id_val: Val = batch_id.into_val(&env);
         let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
         let state_root_val: Val = state_root.into_val(&env);
+        let outputs_hash_val: Val = outputs_hash.into_val(&env);
         env.events().publish(
             ("BATCH_ATTESTED",),
-            (batch_id_val, tee_pubkey_val, state_root_val),
+            (batch_id_val, tee_pubkey_val, state_root_val, outputs_hash_val),
+        );
+
+        Self::notify_subscribers(&env, batch_id, &state_root, &outputs_hash);
+    }
+
+    /// Publish a checkpoint of the sealed, encrypted enclave state after a batch
+    /// Only callable by the TEE whose attestation of the batch stands, the
+    /// signature must be made by tee_pubkey over batch_id || blob_hash
+    /// A small blob may be stored inline and must hash to blob_hash
+    pub fn publish_state_checkpoint(
+        env: Env,
+        tee_pubkey: BytesN<32>,
+        batch_id: u64,
+        blob_hash: BytesN<32>,
+        blob: Option<Bytes>,
+        signature: BytesN<64>,
+    ) {
+        // The checkpoint must come from the TEE that attested the batch
+        let record = match Self::attestation(env.clone(), batch_id) {
+            Some(record) if record.status != AttestationStatus::FAILED => record,
+            _ => panic_with_error!(&env, Error::BatchNotAttested),
+        };
+        if record.tee_pubkey != tee_pubkey {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let key = DataKey::StateCheckpoint(batch_id);
+        if env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::CheckpointAlreadyPublished);
+        }
+
+        // Validate the inline blob against its hash
+        if let Some(blob) = &blob {
+            if blob.len() > MAX_STATE_BLOB_SIZE {
+                panic_with_error!(&env, Error::StateBlobTooLarge);
+            }
+            let hash: BytesN<32> = env.crypto().sha256(blob).into();
+            if hash != blob_hash {
+                panic_with_error!(&env, Error::InvalidStateBlobHash);
+            }
+        }
+
+        // Verify the checkpoint was signed by the TEE attestation key
+        let message = state_checkpoint_message(&env, batch_id, &blob_hash);
+        env.crypto().ed25519_verify(&tee_pubkey, &message, &signature);
+
+        let checkpoint = StateCheckpoint {
+            tee_pubkey: tee_pubkey.clone(),
+            blob_hash: blob_hash.clone(),
+            blob,
+            published_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &checkpoint);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        // Emit event
+        let batch_id_val: Val = batch_id.into_val(&env);
+        let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
+        let blob_hash_val: Val = blob_hash.into_val(&env);
+        env.events().publish(
+            ("STATE_CHECKPOINTED",),
+            (batch_id_val, tee_pubkey_val, blob_hash_val),
         );
     }
 
@@ -235,4 +1348,302 @@ impl OnchainCommitment {
     }
 }
 
+impl OnchainCommitment {
+    /// Check a submitter authorized the call and may add inputs encrypted to
+    /// `epoch` starting at `nonce`
+    /// Returns the storage key of the submitter's next nonce
+    fn validate_submission(env: &Env, submitter: &Address, nonce: u64, epoch: u64) -> DataKey {
+        submitter.require_auth();
+
+        // Permissioned deployments only accept allowlisted submitters
+        if Self::allowlist_enabled(env.clone())
+            && !Self::is_submitter_allowed(env.clone(), submitter.clone())
+        {
+            panic_with_error!(env, Error::SubmitterNotAllowed);
+        }
+
+        // Validate the encryption key epoch
+        let encryption_key = match Self::encryption_key(env.clone(), epoch) {
+            Some(encryption_key) => encryption_key,
+            None => panic_with_error!(env, Error::EpochNotFound),
+        };
+        if env.ledger().timestamp() >= encryption_key.expires_at {
+            panic_with_error!(env, Error::EpochExpired);
+        }
+
+        // Validate the submitter nonce
+        let nonce_key = DataKey::SubmitterNonce(submitter.clone());
+        let expected_nonce: u64 = env.storage().persistent().get(&nonce_key).unwrap_or(0);
+        if nonce != expected_nonce {
+            panic_with_error!(env, Error::InvalidNonce);
+        }
+
+        nonce_key
+    }
+
+    /// Validate a submission and append its commitments to the current batch
+    /// The inputs take consecutive nonces starting at `nonce`
+    /// Returns the batch ID and the index of the first input in the batch
+    fn append_inputs(
+        env: &Env,
+        submitter: &Address,
+        nonce: u64,
+        epoch: u64,
+        encrypted_inputs: Vec<String>,
+    ) -> (u64, u32) {
+        let nonce_key = Self::validate_submission(env, submitter, nonce, epoch);
+        let count = encrypted_inputs.len();
+
+        // Get current batch ID
+        let current_batch_id = Self::current_batch_id(env.clone());
+
+        // Get existing batch inputs
+        let mut batch_storage: Map<u64, Vec<InputCommitment>> = env
+            .storage()
+            .instance()
+            .get(&BATCH_INPUTS)
+            .unwrap_or_else(|| Map::new(env));
+
+        let mut inputs = batch_storage
+            .get(current_batch_id)
+            .unwrap_or_else(|| Vec::new(env));
+        let first_index = inputs.len();
+
+        // Enforce batch capacity and per-submitter caps for the whole submission
+        let limits = Self::batch_limits(env.clone());
+        if limits.max_inputs_per_batch > 0 && first_index + count > limits.max_inputs_per_batch {
+            panic_with_error!(env, Error::BatchFull);
+        }
+
+        let count_key = DataKey::SubmitterInputs(current_batch_id, submitter.clone());
+        let submitter_count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        if limits.max_inputs_per_submitter > 0
+            && submitter_count + count > limits.max_inputs_per_submitter
+        {
+            panic_with_error!(env, Error::SubmitterLimitReached);
+        }
+
+        for (offset, encrypted_data) in encrypted_inputs.iter().enumerate() {
+            inputs.push_back(InputCommitment {
+                encrypted_data,
+                submitter: submitter.clone(),
+                timestamp: env.ledger().timestamp(),
+                withdrawn: false,
+                nonce: nonce + offset as u64,
+                epoch,
+            });
+        }
+        batch_storage.set(current_batch_id, inputs);
+
+        // Persist storage
+        env.storage().instance().set(&BATCH_INPUTS, &batch_storage);
+        env.storage().instance().extend_ttl(100, 100);
+        env.storage()
+            .persistent()
+            .set(&count_key, &(submitter_count + count));
+        env.storage().persistent().extend_ttl(&count_key, 100, 100);
+        env.storage()
+            .persistent()
+            .set(&nonce_key, &(nonce + count as u64));
+        env.storage().persistent().extend_ttl(&nonce_key, 100, 100);
+
+        (current_batch_id, first_index)
+    }
+
+    /// Remove the per-submitter counters of a batch being sealed
+    /// Every counted submitter has at least one input in the batch
+    fn clear_submitter_counts(env: &Env, batch_id: u64) {
+        for input in Self::batch_inputs(env.clone(), batch_id).iter() {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::SubmitterInputs(batch_id, input.submitter));
+        }
+    }
+
+    /// Discard the attestations of all batches after `batch_id` so they can be
+    /// attested again, marking them as reverted
+    /// Returns the IDs of the discarded batches
+    fn discard_attestations_after(
+        env: &Env,
+        attested_batches: &mut Map<u64, bool>,
+        batch_id: u64,
+    ) -> Vec<u64> {
+        // Only attested batches are visited, in ascending order
+        let mut reverted_batches = Vec::<u64>::new(env);
+        for (later_batch_id, _) in attested_batches.clone().iter() {
+            if later_batch_id <= batch_id {
+                continue;
+            }
+
+            attested_batches.remove(later_batch_id);
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Attestation(later_batch_id));
+            env.storage()
+                .persistent()
+                .remove(&DataKey::BatchOutput(later_batch_id));
+            env.storage()
+                .persistent()
+                .remove(&DataKey::StateCheckpoint(later_batch_id));
+            let reverted_key = DataKey::BatchReverted(later_batch_id);
+            env.storage().persistent().set(&reverted_key, &true);
+            env.storage().persistent().extend_ttl(&reverted_key, 100, 100);
+            reverted_batches.push_back(later_batch_id);
+        }
+
+        reverted_batches
+    }
+
+    /// Drop a consumer's subscription
+    /// Returns true if the subscription was removed, false if not subscribed
+    fn remove_subscription(env: &Env, consumer_contract: &Address) -> bool {
+        let mut subscribers = Self::subscribers(env.clone());
+        let index = match subscribers
+            .iter()
+            .position(|subscription| subscription.consumer == *consumer_contract)
+        {
+            Some(index) => index as u32,
+            None => return false,
+        };
+
+        let subscription = subscribers.get(index).unwrap();
+        subscribers.remove(index);
+        env.storage().instance().set(&SUBSCRIBERS, &subscribers);
+        env.storage().instance().extend_ttl(100, 100);
+
+        // Emit event
+        let app_id_val: Val = subscription.app_id.into_val(env);
+        let consumer_val: Val = consumer_contract.into_val(env);
+        env.events()
+            .publish(("CONSUMER_UNSUBSCRIBED",), (app_id_val, consumer_val));
+
+        true
+    }
+
+    /// Slash the operator of a TEE whose attestation was successfully challenged
+    /// The rollback never depends on it: when slashing is not configured, the
+    /// TEE has no operator or the Staking contract fails, SLASH_FAILED is
+    /// emitted instead of TEE_SLASHED
+    fn slash_operator(env: &Env, batch_id: u64, tee_pubkey: &BytesN<32>, challenger: &Address) {
+        let config = Self::slashing_config(env.clone());
+        let operator = Self::tee_operator(env.clone(), tee_pubkey.clone());
+
+        let slashed = match (config, &operator) {
+            (Some(config), Some(operator)) => {
+                let args: Vec<Val> = (
+                    env.current_contract_address(),
+                    operator.clone(),
+                    config.slash_amount,
+                    challenger.clone(),
+                    config.challenger_share_bps,
+                    config.treasury,
+                )
+                    .into_val(env);
+                match env.try_invoke_contract::<i128, InvokeError>(
+                    &config.staking_contract,
+                    &Symbol::new(env, "slash"),
+                    args,
+                ) {
+                    Ok(Ok(slashed)) => Some(slashed),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        // Emit event
+        let batch_id_val: Val = batch_id.into_val(env);
+        let tee_pubkey_val: Val = tee_pubkey.into_val(env);
+        let challenger_val: Val = challenger.into_val(env);
+        match (slashed, operator) {
+            (Some(slashed), Some(operator)) => {
+                let operator_val: Val = operator.into_val(env);
+                let slashed_val: Val = slashed.into_val(env);
+                env.events().publish(
+                    ("TEE_SLASHED",),
+                    (batch_id_val, tee_pubkey_val, operator_val, challenger_val, slashed_val),
+                );
+            }
+            _ => {
+                env.events().publish(
+                    ("SLASH_FAILED",),
+                    (batch_id_val, tee_pubkey_val, challenger_val),
+                );
+            }
+        }
+    }
+
+    /// Call `on_batch_attested` on every subscriber
+    /// A failing consumer is reported with an event and does not affect the others
+    fn notify_subscribers(
+        env: &Env,
+        batch_id: u64,
+        state_root: &BytesN<32>,
+        outputs_hash: &BytesN<32>,
+    ) {
+        let callback = Symbol::new(env, "on_batch_attested");
+        for subscription in Self::subscribers(env.clone()).iter() {
+            let args: Vec<Val> = (batch_id, state_root.clone(), outputs_hash.clone()).into_val(env);
+            let result = env.try_invoke_contract::<Val, InvokeError>(
+                &subscription.consumer,
+                &callback,
+                args,
+            );
+
+            if result.is_err() {
+                let batch_id_val: Val = batch_id.into_val(env);
+                let consumer_val: Val = subscription.consumer.into_val(env);
+                env.events()
+                    .publish(("CALLBACK_FAILED",), (batch_id_val, consumer_val));
+            }
+        }
+    }
+
+    /// Assign the primary and backup TEEs of a sealed batch
+    fn assign_batch(env: &Env, batch_id: u64) {
+        let tees: Map<BytesN<32>, bool> = env
+            .storage()
+            .instance()
+            .get(&REGISTERED_TEES)
+            .unwrap_or_else(|| Map::new(env));
+
+        let mut candidates = Vec::<BytesN<32>>::new(env);
+        for (tee_pubkey, registered) in tees.iter() {
+            if registered {
+                candidates.push_back(tee_pubkey);
+            }
+        }
+
+        // Nothing to assign, any TEE that registers later may attest
+        let count = candidates.len() as u64;
+        if count == 0 {
+            return;
+        }
+
+        let primary = candidates.get((batch_id % count) as u32).unwrap();
+        let backup = candidates.get(((batch_id + 1) % count) as u32).unwrap();
+        let assignment = BatchAssignment {
+            primary: primary.clone(),
+            backup: backup.clone(),
+            assigned_at: env.ledger().sequence(),
+            window: Self::assignment_window(env.clone()),
+        };
+
+        let key = DataKey::BatchAssignment(batch_id);
+        env.storage().persistent().set(&key, &assignment);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        // Emit event
+        let batch_id_val: Val = batch_id.into_val(env);
+        let primary_val: Val = primary.into_val(env);
+        let backup_val: Val = backup.into_val(env);
+        let assigned_at_val: Val = assignment.assigned_at.into_val(env);
+        let window_val: Val = assignment.window.into_val(env);
+        env.events().publish(
+            ("BATCH_ASSIGNED",),
+            (batch_id_val, primary_val, backup_val, assigned_at_val, window_val),
+        );
+    }
+}
+
 mod test;
@@ -1,14 +1,219 @@
 #![cfg(test)]
-use crate::{InputCommitment, OnchainCommitment, OnchainCommitmentClient};
+use crate::{AttestationRecord, DataKey, Error, OnchainCommitment, OnchainCommitmentClient};
+use shared::AttestationStatus;
+use ed25519_dalek::{Signer, SigningKey};
+use exhausting_consumer::ExhaustingConsumer;
+use failing_consumer::FailingConsumer;
+use mock_staking::{MockStaking, MockStakingClient};
+use recording_consumer::{RecordingConsumer, RecordingConsumerClient};
 use soroban_sdk::{
-    log, testutils::Ledger, BytesN, Env, String,
+    symbol_short,
+    testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
+    vec, Address, IntoVal, Bytes, BytesN, Env, String,
 };
 extern crate std;
+use std::panic::AssertUnwindSafe;
+
+/// Register a TEE through the admin, initializing the contract if needed
+fn register_tee(env: &Env, client: &OnchainCommitmentClient, tee_pubkey: &BytesN<32>) -> bool {
+    if client.admin().is_none() {
+        client.initialize(&Address::generate(env));
+    }
+    client.mock_all_auths().register_tee(tee_pubkey)
+}
+
+/// Register a TEE, designate it as key publisher and publish the encryption
+/// key for the next epoch
+fn publish_encryption_key(env: &Env, client: &OnchainCommitmentClient) -> u64 {
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(env, &signing_key.verifying_key().to_bytes());
+    register_tee(env, client, &tee_pubkey);
+    client.mock_all_auths().set_key_publisher(&tee_pubkey);
+
+    let epoch = client.current_epoch() + 1;
+    let public_key = BytesN::from_array(env, &[5u8; 32]);
+    let expires_at = env.ledger().timestamp() + 3600;
+    let signature = sign_encryption_key(&signing_key, epoch, &public_key, expires_at);
+    client.publish_encryption_key(&tee_pubkey, &epoch, &public_key, &expires_at, &signature);
+
+    epoch
+}
+
+/// Derive a TEE attestation keypair from a seed byte
+fn tee_keypair(env: &Env, seed: u8) -> (SigningKey, BytesN<32>) {
+    let signing_key = SigningKey::from_bytes(&[seed; 32]);
+    let tee_pubkey = BytesN::from_array(env, &signing_key.verifying_key().to_bytes());
+    (signing_key, tee_pubkey)
+}
+
+fn sign_attestation(
+    signing_key: &SigningKey,
+    batch_id: u64,
+    state_root: &BytesN<32>,
+    outputs_hash: &BytesN<32>,
+) -> BytesN<64> {
+    let mut message = std::vec::Vec::new();
+    message.extend_from_slice(&batch_id.to_be_bytes());
+    message.extend_from_slice(&state_root.to_array());
+    message.extend_from_slice(&outputs_hash.to_array());
+    BytesN::from_array(state_root.env(), &signing_key.sign(&message).to_bytes())
+}
+
+/// Submit a signed attestation without public output
+fn attest(
+    client: &OnchainCommitmentClient,
+    signing_key: &SigningKey,
+    batch_id: u64,
+    state_root: &BytesN<32>,
+) {
+    let env = state_root.env();
+    let tee_pubkey = BytesN::from_array(env, &signing_key.verifying_key().to_bytes());
+    let outputs_hash = empty_outputs_hash(env);
+    let signature = sign_attestation(signing_key, batch_id, state_root, &outputs_hash);
+    client.submit_attestation(
+        &tee_pubkey,
+        &batch_id,
+        state_root,
+        &None,
+        &outputs_hash,
+        &signature,
+    );
+}
+
+fn sign_encryption_key(
+    signing_key: &SigningKey,
+    epoch: u64,
+    public_key: &BytesN<32>,
+    expires_at: u64,
+) -> BytesN<64> {
+    let mut message = std::vec::Vec::new();
+    message.extend_from_slice(&epoch.to_be_bytes());
+    message.extend_from_slice(&public_key.to_array());
+    message.extend_from_slice(&expires_at.to_be_bytes());
+    BytesN::from_array(public_key.env(), &signing_key.sign(&message).to_bytes())
+}
+
+/// Outputs hash of an attestation without outputs
+fn empty_outputs_hash(env: &Env) -> BytesN<32> {
+    env.crypto().sha256(&Bytes::new(env)).into()
+}
+
+fn sign_state_checkpoint(
+    signing_key: &SigningKey,
+    batch_id: u64,
+    blob_hash: &BytesN<32>,
+) -> BytesN<64> {
+    let mut message = std::vec::Vec::new();
+    message.extend_from_slice(&batch_id.to_be_bytes());
+    message.extend_from_slice(&blob_hash.to_array());
+    BytesN::from_array(blob_hash.env(), &signing_key.sign(&message).to_bytes())
+}
+
+mod recording_consumer {
+    use soroban_sdk::{contract, contractimpl, symbol_short, BytesN, Env};
+
+    /// Consumer that records the last attested batch
+    #[contract]
+    pub struct RecordingConsumer;
+
+    #[contractimpl]
+    impl RecordingConsumer {
+        pub fn on_batch_attested(
+            env: Env,
+            batch_id: u64,
+            state_root: BytesN<32>,
+            outputs_hash: BytesN<32>,
+        ) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("LAST"), &(batch_id, state_root, outputs_hash));
+        }
+
+        pub fn last(env: Env) -> Option<(u64, BytesN<32>, BytesN<32>)> {
+            env.storage().instance().get(&symbol_short!("LAST"))
+        }
+    }
+}
+
+mod mock_staking {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+    /// Staking contract stand-in that records the last slash
+    #[contract]
+    pub struct MockStaking;
+
+    #[contractimpl]
+    impl MockStaking {
+        pub fn slash(
+            env: Env,
+            slasher: Address,
+            operator: Address,
+            amount: i128,
+            challenger: Address,
+            challenger_share_bps: u32,
+            treasury: Address,
+        ) -> i128 {
+            slasher.require_auth();
+            env.storage().instance().set(
+                &symbol_short!("SLASH"),
+                &(operator, amount, challenger, challenger_share_bps, treasury),
+            );
+            amount
+        }
+
+        pub fn last_slash(env: Env) -> Option<(Address, i128, Address, u32, Address)> {
+            env.storage().instance().get(&symbol_short!("SLASH"))
+        }
+    }
+}
+
+mod failing_consumer {
+    use soroban_sdk::{contract, contractimpl, BytesN, Env};
+
+    /// Consumer whose callback always fails
+    #[contract]
+    pub struct FailingConsumer;
+
+    #[contractimpl]
+    impl FailingConsumer {
+        pub fn on_batch_attested(
+            _env: Env,
+            _batch_id: u64,
+            _state_root: BytesN<32>,
+            _outputs_hash: BytesN<32>,
+        ) {
+            panic!("consumer failure");
+        }
+    }
+}
+
+mod exhausting_consumer {
+    use soroban_sdk::{contract, contractimpl, Bytes, BytesN, Env};
+
+    /// Consumer whose callback hashes until the CPU budget runs out
+    #[contract]
+    pub struct ExhaustingConsumer;
+
+    #[contractimpl]
+    impl ExhaustingConsumer {
+        pub fn on_batch_attested(
+            env: Env,
+            _batch_id: u64,
+            _state_root: BytesN<32>,
+            _outputs_hash: BytesN<32>,
+        ) {
+            let data = Bytes::from_array(&env, &[0u8; 1024]);
+            loop {
+                env.crypto().sha256(&data);
+            }
+        }
+    }
+}
 
 #[test]
 fn test_current_batch_id() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
 
     // Initially should be 0
@@ -27,37 +232,46 @@ fn test_current_batch_id() {
 #[test]
 fn test_register_tee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
 
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
     // Create a test TEE public key (32 bytes)
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
+    // Registration requires the admin's authorization
+    assert!(client.try_register_tee(&tee_pubkey).is_err());
+
     // Register TEE
-    let result = client.register_tee(&tee_pubkey);
-    assert_eq!(result, true);
+    let result = client.mock_all_auths().register_tee(&tee_pubkey);
+    assert!(result);
+    assert_eq!(env.auths()[0].0, admin);
 
     // Verify TEE is registered
     let is_registered = client.is_tee_registered(&tee_pubkey);
-    assert_eq!(is_registered, true);
+    assert!(is_registered);
 
     // Try to register again (should return false)
-    let result2 = client.register_tee(&tee_pubkey);
-    assert_eq!(result2, false);
+    let result2 = client.mock_all_auths().register_tee(&tee_pubkey);
+    assert!(!result2);
 }
 
 #[test]
 fn test_submit_encrypted_input() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
 
     // Create a test submitter address
-    let submitter = String::from_str(&env, "GBM3EUBXO6SBBS2JF7RJBXF4EPGWH3TJVTUKWY242RT3EJQAQ6RHXQWE");
+    let submitter = Address::generate(&env);
     let encrypted_data = String::from_str(&env, "0x1234567890abcdef");
 
     // Submit encrypted input (should go to batch 0)
-    let batch_id = client.submit_encrypted_input(&submitter, &encrypted_data);
+    let batch_id = client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
     assert_eq!(batch_id, 0);
 
     // Get batch inputs
@@ -68,22 +282,50 @@ fn test_submit_encrypted_input() {
     let input = inputs.get(0).unwrap();
     assert_eq!(input.encrypted_data, encrypted_data);
     assert_eq!(input.submitter, submitter);
+    assert_eq!(input.nonce, 0);
+
+    // Verify the submitter nonce advanced
+    assert_eq!(client.submitter_nonce(&submitter), 1);
+}
+
+#[test]
+fn test_submit_requires_submitter_auth() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    // Without the submitter's signature neither submit path accepts the input
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x1234567890abcdef");
+    assert!(client
+        .try_submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch)
+        .is_err());
+    let data_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let locator = String::from_str(&env, "");
+    assert!(client
+        .try_submit_input_reference(&submitter, &data_hash, &64, &locator, &0, &epoch)
+        .is_err());
+    assert_eq!(client.submitter_nonce(&submitter), 0);
+    assert_eq!(client.batch_inputs(&0).len(), 0);
 }
 
 #[test]
 fn test_batch_inputs() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
 
     // Submit multiple inputs to batch 0
-    let submitter1 = String::from_str(&env, "GBM3EUBXO6SBBS2JF7RJBXF4EPGWH3TJVTUKWY242RT3EJQAQ6RHXQWE");
-    let submitter2 = String::from_str(&env, "GCNY5OXYSY4FKHOPT2SPOQZAOEIGXB5LBYW3HVU3OWEQOEHFTKQK2MQM");
+    let submitter1 = Address::generate(&env);
+    let submitter2 = Address::generate(&env);
     let encrypted_data1 = String::from_str(&env, "0x1111111111");
     let encrypted_data2 = String::from_str(&env, "0x2222222222");
 
-    client.submit_encrypted_input(&submitter1, &encrypted_data1);
-    client.submit_encrypted_input(&submitter2, &encrypted_data2);
+    client.submit_encrypted_input(&submitter1, &encrypted_data1, &0, &epoch);
+    client.submit_encrypted_input(&submitter2, &encrypted_data2, &0, &epoch);
 
     // Get all inputs for batch 0
     let inputs = client.batch_inputs(&0);
@@ -106,8 +348,10 @@ fn test_batch_inputs() {
 #[test]
 fn test_create_new_batch() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
 
     // Initially batch ID is 0
     assert_eq!(client.current_batch_id(), 0);
@@ -123,16 +367,16 @@ fn test_create_new_batch() {
     assert_eq!(client.current_batch_id(), 2);
 
     // Submit input to new batch
-    let submitter = String::from_str(&env, "GBM3EUBXO6SBBS2JF7RJBXF4EPGWH3TJVTUKWY242RT3EJQAQ6RHXQWE");
+    let submitter = Address::generate(&env);
     let encrypted_data = String::from_str(&env, "0xabcdef");
-    let returned_batch_id = client.submit_encrypted_input(&submitter, &encrypted_data);
+    let returned_batch_id = client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
     assert_eq!(returned_batch_id, 2); // Should go to current batch (2)
 }
 
 #[test]
 fn test_state_root() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
 
     // Initially no state root
@@ -151,27 +395,30 @@ fn test_state_root() {
 #[test]
 fn test_batch_attested() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
 
     // Initially batch is not attested
-    assert_eq!(client.batch_attested(&0), false);
+    assert!(!client.batch_attested(&0));
 
     // Register a TEE
-    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
-    client.register_tee(&tee_pubkey);
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
 
     // Submit some inputs to batch 0
-    let submitter = String::from_str(&env, "GBM3EUBXO6SBBS2JF7RJBXF4EPGWH3TJVTUKWY242RT3EJQAQ6RHXQWE");
+    let submitter = Address::generate(&env);
     let encrypted_data = String::from_str(&env, "0x123456");
-    client.submit_encrypted_input(&submitter, &encrypted_data);
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
 
-    // Attest the batch
+    // Seal and attest the batch
+    client.create_new_batch();
     let state_root = BytesN::from_array(&env, &[99u8; 32]);
-    client.submit_attestation(&tee_pubkey, &0, &state_root);
+    attest(&client, &tee_key, 0, &state_root);
 
     // Verify batch is attested
-    assert_eq!(client.batch_attested(&0), true);
+    assert!(client.batch_attested(&0));
 
     // Verify state root was updated
     let current_root = client.current_state_root();
@@ -179,69 +426,108 @@ fn test_batch_attested() {
 }
 
 #[test]
-#[should_panic(expected = "TeeNotRegistered")]
+#[should_panic(expected = "Error(Contract, #4)")]
 fn test_submit_attestation_with_unregistered_tee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
 
     // Try to attest with unregistered TEE
-    let unregistered_tee = BytesN::from_array(&env, &[99u8; 32]);
+    let (unregistered_key, _) = tee_keypair(&env, 99);
     let state_root = BytesN::from_array(&env, &[42u8; 32]);
-    client.submit_attestation(&unregistered_tee, &0, &state_root);
+    attest(&client, &unregistered_key, 0, &state_root);
+}
+
+#[test]
+fn test_submit_attestation_unsealed_batch() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+
+    // Batch 0 is still open, and batch 1 does not exist yet
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    let outputs_hash = empty_outputs_hash(&env);
+    for batch_id in [0u64, 1] {
+        let signature = sign_attestation(&tee_key, batch_id, &state_root, &outputs_hash);
+        assert_eq!(
+            client.try_submit_attestation(
+                &tee_pubkey,
+                &batch_id,
+                &state_root,
+                &None,
+                &outputs_hash,
+                &signature,
+            ),
+            Err(Ok(Error::BatchNotSealed.into()))
+        );
+    }
+    assert!(!client.batch_attested(&0));
+
+    // Once sealed, the batch can be attested
+    client.create_new_batch();
+    attest(&client, &tee_key, 0, &state_root);
+    assert!(client.batch_attested(&0));
 }
 
 #[test]
-#[should_panic(expected = "BatchAlreadyAttested")]
+#[should_panic(expected = "Error(Contract, #5)")]
 fn test_submit_attestation_twice() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
 
     // Register TEE
-    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
-    client.register_tee(&tee_pubkey);
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
 
     // Attest batch 0
     let state_root1 = BytesN::from_array(&env, &[42u8; 32]);
-    client.submit_attestation(&tee_pubkey, &0, &state_root1);
+    attest(&client, &tee_key, 0, &state_root1);
 
     // Try to attest the same batch again (should panic)
     let state_root2 = BytesN::from_array(&env, &[99u8; 32]);
-    client.submit_attestation(&tee_pubkey, &0, &state_root2);
+    attest(&client, &tee_key, 0, &state_root2);
 }
 
 #[test]
-#[should_panic(expected = "InvalidEncryptedData")]
+#[should_panic(expected = "Error(Contract, #1)")]
 fn test_submit_empty_encrypted_input() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
 
-    let submitter = String::from_str(&env, "GBM3EUBXO6SBBS2JF7RJBXF4EPGWH3TJVTUKWY242RT3EJQAQ6RHXQWE");
+    let submitter = Address::generate(&env);
     let empty_data = String::from_str(&env, "");
-    client.submit_encrypted_input(&submitter, &empty_data);
+    client.submit_encrypted_input(&submitter, &empty_data, &0, &epoch);
 }
 
 #[test]
 fn test_full_workflow() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, OnchainCommitment);
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
     let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
 
     // 1. Register TEE
-    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
-    assert_eq!(client.register_tee(&tee_pubkey), true);
-    assert_eq!(client.is_tee_registered(&tee_pubkey), true);
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    assert!(register_tee(&env, &client, &tee_pubkey));
+    assert!(client.is_tee_registered(&tee_pubkey));
 
     // 2. Submit multiple encrypted inputs to batch 0
-    let submitter1 = String::from_str(&env, "GBM3EUBXO6SBBS2JF7RJBXF4EPGWH3TJVTUKWY242RT3EJQAQ6RHXQWE");
-    let submitter2 = String::from_str(&env, "GCNY5OXYSY4FKHOPT2SPOQZAOEIGXB5LBYW3HVU3OWEQOEHFTKQK2MQM");
+    let submitter1 = Address::generate(&env);
+    let submitter2 = Address::generate(&env);
     let data1 = String::from_str(&env, "0x111111");
     let data2 = String::from_str(&env, "0x222222");
 
-    let batch_id1 = client.submit_encrypted_input(&submitter1, &data1);
-    let batch_id2 = client.submit_encrypted_input(&submitter2, &data2);
+    let batch_id1 = client.submit_encrypted_input(&submitter1, &data1, &0, &epoch);
+    let batch_id2 = client.submit_encrypted_input(&submitter2, &data2, &0, &epoch);
     assert_eq!(batch_id1, 0);
     assert_eq!(batch_id2, 0);
 
@@ -249,22 +535,21 @@ fn test_full_workflow() {
     let inputs = client.batch_inputs(&0);
     assert_eq!(inputs.len(), 2);
 
-    // 4. Attest the batch
-    let state_root = BytesN::from_array(&env, &[123u8; 32]);
-    client.submit_attestation(&tee_pubkey, &0, &state_root);
-
-    // 5. Verify batch is attested
-    assert_eq!(client.batch_attested(&0), true);
-    assert_eq!(client.current_state_root(), Some(state_root));
-
-    // 6. Create new batch and submit to it
+    // 4. Seal the batch by creating a new one
     let new_batch_id = client.create_new_batch();
     assert_eq!(new_batch_id, 1);
     assert_eq!(client.current_batch_id(), 1);
 
-    let submitter3 = String::from_str(&env, "GDQERENWDSGE6TH6PIN5EJ5ABMFQQJDBHH5XKCDD5DUCKG6GLCTQIGLL");
+    // 5. Attest the sealed batch
+    let state_root = BytesN::from_array(&env, &[123u8; 32]);
+    attest(&client, &tee_key, 0, &state_root);
+    assert!(client.batch_attested(&0));
+    assert_eq!(client.current_state_root(), Some(state_root));
+
+    // 6. Submit to the new batch
+    let submitter3 = Address::generate(&env);
     let data3 = String::from_str(&env, "0x333333");
-    let batch_id3 = client.submit_encrypted_input(&submitter3, &data3);
+    let batch_id3 = client.submit_encrypted_input(&submitter3, &data3, &0, &epoch);
     assert_eq!(batch_id3, 1);
 
     // 7. Verify new batch inputs
@@ -272,3 +557,1355 @@ fn test_full_workflow() {
     assert_eq!(new_batch_inputs.len(), 1);
     assert_eq!(new_batch_inputs.get(0).unwrap().encrypted_data, data3);
 }
+
+#[test]
+fn test_withdraw_input() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter1 = Address::generate(&env);
+    let submitter2 = Address::generate(&env);
+    let data1 = String::from_str(&env, "0x111111");
+    let data2 = String::from_str(&env, "0x222222");
+    client.submit_encrypted_input(&submitter1, &data1, &0, &epoch);
+    client.submit_encrypted_input(&submitter2, &data2, &0, &epoch);
+
+    // Withdraw the first input
+    client.withdraw_input(&0, &0);
+
+    // Verify the original submitter authorized the withdrawal
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, submitter1.clone());
+
+    // Verify the input was tombstoned and the other input is untouched
+    let inputs = client.batch_inputs(&0);
+    assert_eq!(inputs.len(), 2);
+    let withdrawn = inputs.get(0).unwrap();
+    assert!(withdrawn.withdrawn);
+    assert_eq!(withdrawn.encrypted_data, String::from_str(&env, ""));
+    assert_eq!(withdrawn.submitter, submitter1);
+    let kept = inputs.get(1).unwrap();
+    assert!(!kept.withdrawn);
+    assert_eq!(kept.encrypted_data, data2);
+}
+
+#[test]
+fn test_withdraw_input_requires_submitter_auth() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let data = String::from_str(&env, "0x111111");
+    client
+        .mock_all_auths()
+        .submit_encrypted_input(&submitter, &data, &0, &epoch);
+
+    // The stored submitter address is checked directly, so a missing
+    // signature is rejected instead of trapping on address parsing
+    assert!(client.try_withdraw_input(&0, &0).is_err());
+    assert!(!client.batch_inputs(&0).get(0).unwrap().withdrawn);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_withdraw_input_after_batch_sealed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+
+    // Seal batch 0 by moving to batch 1
+    client.create_new_batch();
+
+    // Try to withdraw from the sealed batch (should panic)
+    client.withdraw_input(&0, &0);
+}
+
+#[test]
+fn test_withdraw_input_after_batch_attested() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+
+    // An attestation recorded for the batch freezes its inputs
+    let (_, tee_pubkey) = tee_keypair(&env, 1);
+    let record = AttestationRecord {
+        tee_pubkey,
+        state_root: BytesN::from_array(&env, &[99u8; 32]),
+        status: AttestationStatus::PROPOSED,
+        attested_at: 0,
+    };
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&DataKey::Attestation(0), &record);
+    });
+
+    assert_eq!(
+        client.try_withdraw_input(&0, &0),
+        Err(Ok(Error::BatchAlreadyAttested.into()))
+    );
+    assert!(!client.batch_inputs(&0).get(0).unwrap().withdrawn);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_withdraw_input_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+
+    client.withdraw_input(&0, &0);
+
+    // Try to withdraw the same input again (should panic)
+    client.withdraw_input(&0, &0);
+}
+
+#[test]
+fn test_set_batch_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(client.admin(), Some(admin.clone()));
+
+    // Initially no caps are configured
+    let limits = client.batch_limits();
+    assert_eq!(limits.max_inputs_per_batch, 0);
+    assert_eq!(limits.max_inputs_per_submitter, 0);
+
+    client.set_batch_limits(&10, &2);
+
+    // Verify the admin authorized the change
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, admin);
+
+    let limits = client.batch_limits();
+    assert_eq!(limits.max_inputs_per_batch, 10);
+    assert_eq!(limits.max_inputs_per_submitter, 2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn test_initialize_twice() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+    client.initialize(&Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_submitter_limit_reached() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env));
+    let epoch = publish_encryption_key(&env, &client);
+
+    client.set_batch_limits(&0, &2);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+    client.submit_encrypted_input(&submitter, &encrypted_data, &1, &epoch);
+    assert_eq!(client.submitter_input_count(&0, &submitter), 2);
+
+    // Other submitters are not affected
+    let other = Address::generate(&env);
+    client.submit_encrypted_input(&other, &encrypted_data, &0, &epoch);
+
+    // Counters are cleared when the batch is sealed
+    client.create_new_batch();
+    assert_eq!(client.submitter_input_count(&0, &submitter), 0);
+    assert_eq!(client.submitter_input_count(&0, &other), 0);
+    client.submit_encrypted_input(&submitter, &encrypted_data, &2, &epoch);
+    client.submit_encrypted_input(&submitter, &encrypted_data, &3, &epoch);
+
+    // Third input in the same batch (should panic)
+    client.submit_encrypted_input(&submitter, &encrypted_data, &4, &epoch);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_batch_full() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env));
+    let epoch = publish_encryption_key(&env, &client);
+
+    client.set_batch_limits(&2, &0);
+
+    let submitter1 = Address::generate(&env);
+    let submitter2 = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter1, &encrypted_data, &0, &epoch);
+    client.submit_encrypted_input(&submitter2, &encrypted_data, &0, &epoch);
+
+    // Batch is at capacity (should panic)
+    client.submit_encrypted_input(&submitter1, &encrypted_data, &1, &epoch);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #14)")]
+fn test_submit_replayed_nonce() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+
+    // Replay the same input in a new batch (should panic)
+    client.create_new_batch();
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+}
+
+#[test]
+fn test_nonce_cannot_be_consumed_by_another_party() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let victim = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+
+    // The attacker signs a submission naming the victim as submitter
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "submit_encrypted_input",
+                args: (victim.clone(), encrypted_data.clone(), 0u64, epoch).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_submit_encrypted_input(&victim, &encrypted_data, &0, &epoch);
+    assert!(result.is_err());
+
+    // The victim's nonce is untouched and still usable by the victim
+    assert_eq!(client.submitter_nonce(&victim), 0);
+    client
+        .mock_all_auths()
+        .submit_encrypted_input(&victim, &encrypted_data, &0, &epoch);
+    assert_eq!(client.submitter_nonce(&victim), 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #14)")]
+fn test_submit_skipped_nonce() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+
+    // Nonces must start at 0 (should panic)
+    client.submit_encrypted_input(&submitter, &encrypted_data, &1, &epoch);
+}
+
+#[test]
+fn test_encryption_key_epochs() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    // Initially no key is published
+    assert_eq!(client.current_epoch(), 0);
+    assert_eq!(client.current_encryption_key(), None);
+
+    let epoch1 = publish_encryption_key(&env, &client);
+    assert_eq!(epoch1, 1);
+    let epoch2 = publish_encryption_key(&env, &client);
+    assert_eq!(epoch2, 2);
+
+    // Verify the views
+    assert_eq!(client.current_epoch(), 2);
+    let current = client.current_encryption_key().unwrap();
+    assert_eq!(current.epoch, 2);
+    assert_eq!(current.public_key, BytesN::from_array(&env, &[5u8; 32]));
+    assert_eq!(current.expires_at, 3600);
+    assert_eq!(client.encryption_key(&1).unwrap().epoch, 1);
+    assert_eq!(client.encryption_key(&3), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #32)")]
+fn test_publish_encryption_key_not_publisher() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    publish_encryption_key(&env, &client);
+
+    // Another registered TEE cannot claim the next epoch (should panic)
+    let signing_key = SigningKey::from_bytes(&[8u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &tee_pubkey);
+    let public_key = BytesN::from_array(&env, &[6u8; 32]);
+    let signature = sign_encryption_key(&signing_key, 2, &public_key, 3600);
+    client.publish_encryption_key(&tee_pubkey, &2, &public_key, &3600, &signature);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #15)")]
+fn test_publish_encryption_key_out_of_order() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &tee_pubkey);
+    client.mock_all_auths().set_key_publisher(&tee_pubkey);
+
+    // Epochs start at 1, skipping ahead is rejected (should panic)
+    let public_key = BytesN::from_array(&env, &[5u8; 32]);
+    let signature = sign_encryption_key(&signing_key, 2, &public_key, 3600);
+    client.publish_encryption_key(&tee_pubkey, &2, &public_key, &3600, &signature);
+}
+
+#[test]
+#[should_panic]
+fn test_publish_encryption_key_bad_signature() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &tee_pubkey);
+    client.mock_all_auths().set_key_publisher(&tee_pubkey);
+
+    // Signature over a different key (should panic)
+    let public_key = BytesN::from_array(&env, &[5u8; 32]);
+    let other_key = BytesN::from_array(&env, &[6u8; 32]);
+    let signature = sign_encryption_key(&signing_key, 1, &other_key, 3600);
+    client.publish_encryption_key(&tee_pubkey, &1, &public_key, &3600, &signature);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #16)")]
+fn test_submit_unknown_epoch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+
+    // No key has been published for epoch 1 (should panic)
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #17)")]
+fn test_submit_expired_epoch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+
+    // Move past the key expiry
+    env.ledger().with_mut(|li| li.timestamp = 3600);
+
+    // Encrypted to an expired epoch (should panic)
+    client.submit_encrypted_input(&submitter, &encrypted_data, &1, &epoch);
+}
+
+#[test]
+fn test_batch_assignment() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    // No TEEs registered, nothing to assign
+    client.create_new_batch();
+    assert_eq!(client.batch_assignment(&0), None);
+
+    let tee1 = BytesN::from_array(&env, &[1u8; 32]);
+    let tee2 = BytesN::from_array(&env, &[2u8; 32]);
+    let tee3 = BytesN::from_array(&env, &[3u8; 32]);
+    register_tee(&env, &client, &tee1);
+    register_tee(&env, &client, &tee2);
+    register_tee(&env, &client, &tee3);
+
+    // Seal batches 1 and 2, assignments rotate round-robin
+    client.create_new_batch();
+    client.create_new_batch();
+
+    let assignment1 = client.batch_assignment(&1).unwrap();
+    assert_eq!(assignment1.primary, tee2);
+    assert_eq!(assignment1.backup, tee3);
+    assert_eq!(assignment1.window, 100);
+
+    let assignment2 = client.batch_assignment(&2).unwrap();
+    assert_eq!(assignment2.primary, tee3);
+    assert_eq!(assignment2.backup, tee1);
+
+    // The open batch has no assignment yet
+    assert_eq!(client.batch_assignment(&3), None);
+}
+
+#[test]
+#[should_panic]
+fn test_submit_attestation_bad_signature() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (_, tee_pubkey) = tee_keypair(&env, 1);
+    let (other_key, _) = tee_keypair(&env, 2);
+    register_tee(&env, &client, &tee_pubkey);
+
+    // Signed by a key other than the registered TEE key (should panic)
+    let state_root = BytesN::from_array(&env, &[42u8; 32]);
+    let signature = sign_attestation(&other_key, 0, &state_root, &empty_outputs_hash(&env));
+    client.submit_attestation(
+        &tee_pubkey,
+        &0,
+        &state_root,
+        &None,
+        &empty_outputs_hash(&env),
+        &signature,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn test_submit_attestation_by_unassigned_tee() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (tee1_key, tee1) = tee_keypair(&env, 1);
+    let (tee2_key, tee2) = tee_keypair(&env, 2);
+    register_tee(&env, &client, &tee1);
+    register_tee(&env, &client, &tee2);
+
+    // Seal batch 0, one TEE is primary and the other backup
+    client.create_new_batch();
+    let assignment = client.batch_assignment(&0).unwrap();
+    let backup_key = if assignment.backup == tee1 { &tee1_key } else { &tee2_key };
+
+    // Backup tries to attest inside the primary window (should panic)
+    let state_root = BytesN::from_array(&env, &[42u8; 32]);
+    attest(&client, backup_key, 0, &state_root);
+}
+
+#[test]
+fn test_backup_takes_over_after_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+    client.set_assignment_window(&10);
+
+    let (tee1_key, tee1) = tee_keypair(&env, 1);
+    let (tee2_key, tee2) = tee_keypair(&env, 2);
+    register_tee(&env, &client, &tee1);
+    register_tee(&env, &client, &tee2);
+
+    // Seal batch 0, one TEE is primary and the other backup
+    client.create_new_batch();
+    let assignment = client.batch_assignment(&0).unwrap();
+    assert_ne!(assignment.primary, assignment.backup);
+    assert_eq!(assignment.window, 10);
+    let backup_key = if assignment.backup == tee1 { &tee1_key } else { &tee2_key };
+
+    // Primary window has passed
+    env.ledger().with_mut(|li| li.sequence_number = assignment.assigned_at + 10);
+
+    let state_root = BytesN::from_array(&env, &[42u8; 32]);
+    attest(&client, backup_key, 0, &state_root);
+    assert!(client.batch_attested(&0));
+}
+
+#[test]
+fn test_subscribe() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let consumer = env.register(RecordingConsumer, ());
+    let app_id = symbol_short!("voting");
+
+    assert!(client.subscribe(&app_id, &consumer));
+
+    // Verify the admin approved and the consumer authorized the subscription
+    let auths = env.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!(auths[0].0, admin);
+    assert_eq!(auths[1].0, consumer);
+
+    // Subscribing twice is a no-op
+    assert!(!client.subscribe(&app_id, &consumer));
+    assert_eq!(client.subscribers().len(), 1);
+
+    assert!(client.unsubscribe(&consumer));
+    assert!(!client.unsubscribe(&consumer));
+    assert_eq!(client.subscribers().len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #19)")]
+fn test_subscribe_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    let app_id = symbol_short!("voting");
+    for _ in 0..10 {
+        client.subscribe(&app_id, &Address::generate(&env));
+    }
+
+    // One subscriber too many (should panic)
+    client.subscribe(&app_id, &Address::generate(&env));
+}
+
+#[test]
+fn test_subscribers_notified_on_attestation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    // A failing consumer is subscribed before the working one
+    let failing = env.register(FailingConsumer, ());
+    let recording = env.register(RecordingConsumer, ());
+    let recording_client = RecordingConsumerClient::new(&env, &recording);
+    client.subscribe(&symbol_short!("broken"), &failing);
+    client.subscribe(&symbol_short!("voting"), &recording);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    attest(&client, &tee_key, 0, &state_root);
+
+    // The failure was isolated and the other consumer was notified
+    assert!(client.batch_attested(&0));
+    let (batch_id, notified_root, outputs_hash) = recording_client.last().unwrap();
+    assert_eq!(batch_id, 0);
+    assert_eq!(notified_root, state_root);
+    assert_eq!(outputs_hash, empty_outputs_hash(&env));
+}
+
+#[test]
+fn test_remove_exhausting_subscriber() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    let exhausting = env.register(ExhaustingConsumer, ());
+    client.subscribe(&symbol_short!("greedy"), &exhausting);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+
+    // Running out of budget cannot be caught, so every attestation fails
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    let outputs_hash = empty_outputs_hash(&env);
+    let signature = sign_attestation(&tee_key, 0, &state_root, &outputs_hash);
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        client.submit_attestation(
+            &tee_pubkey,
+            &0,
+            &state_root,
+            &None,
+            &outputs_hash,
+            &signature,
+        )
+    }));
+    assert!(result.is_err());
+    env.budget().reset_default();
+    assert!(!client.batch_attested(&0));
+
+    // The admin evicts the consumer and attestations go through again
+    assert!(client.remove_subscriber(&exhausting));
+    assert!(!client.remove_subscriber(&exhausting));
+    assert_eq!(client.subscribers().len(), 0);
+    attest(&client, &tee_key, 0, &state_root);
+    assert!(client.batch_attested(&0));
+}
+
+#[test]
+fn test_upheld_challenge_slashes_operator() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let staking = env.register(MockStaking, ());
+    let staking_client = MockStakingClient::new(&env, &staking);
+    let treasury = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let challenger = Address::generate(&env);
+
+    client.initialize(&Address::generate(&env));
+    client.set_slashing_config(&staking, &treasury, &500, &2000);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.set_tee_operator(&tee_pubkey, &operator);
+    client.create_new_batch();
+
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    attest(&client, &tee_key, 0, &state_root);
+    assert_eq!(client.attestation(&0).unwrap().status, AttestationStatus::PROPOSED);
+
+    client.resolve_challenge(&0, &challenger, &true);
+
+    // Attestation failed and the operator was slashed
+    let record = client.attestation(&0).unwrap();
+    assert_eq!(record.status, AttestationStatus::FAILED);
+    assert_eq!(record.tee_pubkey, tee_pubkey);
+    assert_eq!(
+        staking_client.last_slash(),
+        Some((operator, 500, challenger, 2000, treasury))
+    );
+}
+
+#[test]
+fn test_upheld_challenge_rolls_back_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let staking = env.register(MockStaking, ());
+    let challenger = Address::generate(&env);
+    client.initialize(&Address::generate(&env));
+    client.set_slashing_config(&staking, &Address::generate(&env), &500, &2000);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.set_tee_operator(&tee_pubkey, &Address::generate(&env));
+
+    let root0 = BytesN::from_array(&env, &[10u8; 32]);
+    let root1 = BytesN::from_array(&env, &[11u8; 32]);
+    let root2 = BytesN::from_array(&env, &[12u8; 32]);
+    for (batch_id, root) in [(0, &root0), (1, &root1), (2, &root2)] {
+        client.create_new_batch();
+        attest(&client, &tee_key, batch_id, root);
+    }
+
+    // Batch 1 had a bad state root, batch 2 was built on top of it
+    client.resolve_challenge(&1, &challenger, &true);
+    assert_eq!(client.current_state_root(), Some(root0.clone()));
+    assert_eq!(client.latest_finalized_batch(), Some(0));
+    assert!(!client.batch_attested(&1));
+    assert!(!client.batch_attested(&2));
+    assert!(client.batch_reverted(&2));
+    assert_eq!(client.attestation(&1).unwrap().status, AttestationStatus::FAILED);
+    assert_eq!(client.state_root_at(&1), None);
+
+    // The batch can be attested again with the correct root
+    let fixed_root1 = BytesN::from_array(&env, &[21u8; 32]);
+    attest(&client, &tee_key, 1, &fixed_root1);
+    assert_eq!(client.current_state_root(), Some(fixed_root1));
+    assert_eq!(client.latest_finalized_batch(), Some(1));
+
+    // Without an earlier valid batch the head is cleared
+    client.resolve_challenge(&1, &challenger, &true);
+    client.resolve_challenge(&0, &challenger, &true);
+    assert_eq!(client.current_state_root(), None);
+    assert_eq!(client.latest_finalized_batch(), None);
+    assert!(!client.batch_attested(&0));
+}
+
+#[test]
+fn test_upheld_challenge_without_slashing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let challenger = Address::generate(&env);
+    client.initialize(&Address::generate(&env));
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    let root0 = BytesN::from_array(&env, &[10u8; 32]);
+    let root1 = BytesN::from_array(&env, &[11u8; 32]);
+    for (batch_id, root) in [(0, &root0), (1, &root1)] {
+        client.create_new_batch();
+        attest(&client, &tee_key, batch_id, root);
+    }
+
+    // Neither slashing nor the operator is configured
+    client.resolve_challenge(&1, &challenger, &true);
+    assert_eq!(client.attestation(&1).unwrap().status, AttestationStatus::FAILED);
+    assert_eq!(client.current_state_root(), Some(root0));
+    assert!(!client.batch_attested(&1));
+
+    // The Staking contract rejects the slash
+    let broken_staking = env.register(FailingConsumer, ());
+    client.set_slashing_config(&broken_staking, &Address::generate(&env), &500, &2000);
+    client.set_tee_operator(&tee_pubkey, &Address::generate(&env));
+    client.resolve_challenge(&0, &challenger, &true);
+    assert_eq!(client.attestation(&0).unwrap().status, AttestationStatus::FAILED);
+    assert_eq!(client.current_state_root(), None);
+    assert!(!client.batch_attested(&0));
+}
+
+#[test]
+fn test_set_slashing_config_invalid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let staking = env.register(MockStaking, ());
+    let treasury = Address::generate(&env);
+    client.initialize(&Address::generate(&env));
+
+    assert_eq!(
+        client.try_set_slashing_config(&staking, &treasury, &-1, &2000),
+        Err(Ok(Error::InvalidSlashingConfig.into()))
+    );
+    assert_eq!(
+        client.try_set_slashing_config(&staking, &treasury, &500, &10_001),
+        Err(Ok(Error::InvalidSlashingConfig.into()))
+    );
+    assert_eq!(client.slashing_config(), None);
+
+    // The whole slash may go to the challenger
+    client.set_slashing_config(&staking, &treasury, &500, &10_000);
+    assert_eq!(client.slashing_config().unwrap().challenger_share_bps, 10_000);
+}
+
+#[test]
+fn test_rejected_challenge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    attest(&client, &tee_key, 0, &state_root);
+
+    client.resolve_challenge(&0, &Address::generate(&env), &false);
+    assert_eq!(client.attestation(&0).unwrap().status, AttestationStatus::SUCCESS);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #20)")]
+fn test_resolve_challenge_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    attest(&client, &tee_key, 0, &state_root);
+
+    client.resolve_challenge(&0, &Address::generate(&env), &false);
+
+    // The attestation is no longer PROPOSED (should panic)
+    client.resolve_challenge(&0, &Address::generate(&env), &true);
+}
+
+#[test]
+fn test_revert_to_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+
+    // Attest batches 0, 1 and 2
+    let root0 = BytesN::from_array(&env, &[10u8; 32]);
+    let root1 = BytesN::from_array(&env, &[11u8; 32]);
+    let root2 = BytesN::from_array(&env, &[12u8; 32]);
+    client.create_new_batch();
+    attest(&client, &tee_key, 0, &root0);
+    client.create_new_batch();
+    attest(&client, &tee_key, 1, &root1);
+    client.create_new_batch();
+    attest(&client, &tee_key, 2, &root2);
+    assert_eq!(client.current_state_root(), Some(root2));
+
+    client.revert_to_batch(&0);
+
+    // State root restored and later batches re-opened
+    assert_eq!(client.current_state_root(), Some(root0.clone()));
+    assert_eq!(client.latest_finalized_batch(), Some(0));
+    assert_eq!(client.state_root_at(&1), None);
+    assert!(client.batch_attested(&0));
+    assert!(!client.batch_attested(&1));
+    assert!(!client.batch_attested(&2));
+    assert!(client.batch_reverted(&1));
+    assert!(client.batch_reverted(&2));
+    assert!(!client.batch_reverted(&0));
+    assert_eq!(client.attestation(&1), None);
+    assert_eq!(client.batch_output(&1), None);
+
+    // A reverted batch can be attested again
+    let new_root1 = BytesN::from_array(&env, &[21u8; 32]);
+    attest(&client, &tee_key, 1, &new_root1);
+    assert_eq!(client.current_state_root(), Some(new_root1));
+    assert!(!client.batch_reverted(&1));
+    assert!(client.batch_reverted(&2));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #23)")]
+fn test_revert_to_unattested_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+
+    // Batch 0 has no attestation (should panic)
+    client.revert_to_batch(&0);
+}
+
+#[test]
+fn test_state_root_history() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    // Initially nothing is finalized
+    assert_eq!(client.latest_finalized_batch(), None);
+    assert_eq!(client.state_root_at(&0), None);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+
+    let root0 = BytesN::from_array(&env, &[10u8; 32]);
+    let root1 = BytesN::from_array(&env, &[11u8; 32]);
+    client.create_new_batch();
+    client.create_new_batch();
+
+    // Batch 1 is attested before batch 0
+    attest(&client, &tee_key, 1, &root1);
+    attest(&client, &tee_key, 0, &root0);
+
+    // Every batch keeps its own root
+    assert_eq!(client.state_root_at(&0), Some(root0));
+    assert_eq!(client.state_root_at(&1), Some(root1.clone()));
+    assert_eq!(client.state_root_at(&2), None);
+
+    // The late attestation of batch 0 does not move the head back
+    assert_eq!(client.latest_finalized_batch(), Some(1));
+    assert_eq!(client.current_state_root(), Some(root1));
+}
+
+#[test]
+fn test_batch_output() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+
+    client.create_new_batch();
+
+    // Initially no output
+    assert_eq!(client.batch_output(&0), None);
+
+    // Attest with a public vote tally
+    let tally = Bytes::from_slice(&env, b"yes=12;no=7");
+    let outputs_hash: BytesN<32> = env.crypto().sha256(&tally).into();
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    let signature = sign_attestation(&tee_key, 0, &state_root, &outputs_hash);
+    client.submit_attestation(
+        &tee_pubkey,
+        &0,
+        &state_root,
+        &Some(tally.clone()),
+        &outputs_hash,
+        &signature,
+    );
+
+    let output = client.batch_output(&0).unwrap();
+    assert_eq!(output.public_output, Some(tally));
+    assert_eq!(output.outputs_hash, outputs_hash);
+}
+
+#[test]
+#[should_panic]
+fn test_batch_output_not_signed() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+
+    // The TEE signed an empty output, a relayer swaps in another one (should panic)
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    let signature = sign_attestation(&tee_key, 0, &state_root, &empty_outputs_hash(&env));
+    let tally = Bytes::from_slice(&env, b"yes=0;no=19");
+    let outputs_hash: BytesN<32> = env.crypto().sha256(&tally).into();
+    client.submit_attestation(
+        &tee_pubkey,
+        &0,
+        &state_root,
+        &Some(tally),
+        &outputs_hash,
+        &signature,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #25)")]
+fn test_batch_output_hash_mismatch() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+
+    // Output does not match the attested hash (should panic)
+    let tally = Bytes::from_slice(&env, b"yes=12;no=7");
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    let signature = sign_attestation(&tee_key, 0, &state_root, &empty_outputs_hash(&env));
+    client.submit_attestation(
+        &tee_pubkey,
+        &0,
+        &state_root,
+        &Some(tally),
+        &empty_outputs_hash(&env),
+        &signature,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #24)")]
+fn test_batch_output_too_large() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let (tee_key, tee_pubkey) = tee_keypair(&env, 1);
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+
+    // Output exceeds the size cap (should panic)
+    let output = Bytes::from_array(&env, &[0u8; 1025]);
+    let outputs_hash: BytesN<32> = env.crypto().sha256(&output).into();
+    let state_root = BytesN::from_array(&env, &[99u8; 32]);
+    let signature = sign_attestation(&tee_key, 0, &state_root, &outputs_hash);
+    client.submit_attestation(
+        &tee_pubkey,
+        &0,
+        &state_root,
+        &Some(output),
+        &outputs_hash,
+        &signature,
+    );
+}
+
+#[test]
+fn test_state_checkpoint() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &tee_pubkey);
+
+    client.create_new_batch();
+    let state_root = BytesN::from_array(&env, &[10u8; 32]);
+    attest(&client, &signing_key, 0, &state_root);
+    assert_eq!(client.state_blob_hash(&0), None);
+
+    // Publish a small blob inline
+    let blob = Bytes::from_array(&env, &[1, 2, 3, 4]);
+    let blob_hash: BytesN<32> = env.crypto().sha256(&blob).into();
+    let signature = sign_state_checkpoint(&signing_key, 0, &blob_hash);
+    client.publish_state_checkpoint(&tee_pubkey, &0, &blob_hash, &Some(blob.clone()), &signature);
+
+    assert_eq!(client.state_blob_hash(&0), Some(blob_hash.clone()));
+    let checkpoint = client.state_checkpoint(&0).unwrap();
+    assert_eq!(checkpoint.tee_pubkey, tee_pubkey);
+    assert_eq!(checkpoint.blob, Some(blob));
+
+    // Discarded when the batch is reverted
+    client.create_new_batch();
+    attest(&client, &signing_key, 1, &state_root);
+    let signature = sign_state_checkpoint(&signing_key, 1, &blob_hash);
+    client.publish_state_checkpoint(&tee_pubkey, &1, &blob_hash, &None, &signature);
+    assert_eq!(client.state_blob_hash(&1), Some(blob_hash));
+
+    env.mock_all_auths();
+    client.revert_to_batch(&0);
+    assert_eq!(client.state_blob_hash(&1), None);
+    assert!(client.state_blob_hash(&0).is_some());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_state_checkpoint_from_other_tee() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    let other_key = SigningKey::from_bytes(&[8u8; 32]);
+    let other_pubkey = BytesN::from_array(&env, &other_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &tee_pubkey);
+    client.create_new_batch();
+    register_tee(&env, &client, &other_pubkey);
+    let state_root = BytesN::from_array(&env, &[10u8; 32]);
+    attest(&client, &signing_key, 0, &state_root);
+
+    let blob_hash = BytesN::from_array(&env, &[3u8; 32]);
+    let signature = sign_state_checkpoint(&other_key, 0, &blob_hash);
+    client.publish_state_checkpoint(&other_pubkey, &0, &blob_hash, &None, &signature);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #27)")]
+fn test_state_checkpoint_hash_mismatch() {
+    let env = Env::default();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &tee_pubkey);
+
+    client.create_new_batch();
+    let state_root = BytesN::from_array(&env, &[10u8; 32]);
+    attest(&client, &signing_key, 0, &state_root);
+
+    let blob = Bytes::from_array(&env, &[1, 2, 3, 4]);
+    let blob_hash = BytesN::from_array(&env, &[3u8; 32]);
+    let signature = sign_state_checkpoint(&signing_key, 0, &blob_hash);
+    client.publish_state_checkpoint(&tee_pubkey, &0, &blob_hash, &Some(blob), &signature);
+}
+
+#[test]
+fn test_submitter_allowlist() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let member = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x1234567890abcdef");
+
+    assert!(!client.allowlist_enabled());
+    client.set_allowlist_enabled(&true);
+    assert!(client.allowlist_enabled());
+
+    // Only the admin manages the allowlist
+    assert!(client.add_submitter(&member));
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, admin);
+    assert!(!client.add_submitter(&member));
+    assert!(client.is_submitter_allowed(&member));
+
+    client.submit_encrypted_input(&member, &encrypted_data, &0, &epoch);
+    assert_eq!(client.batch_inputs(&0).len(), 1);
+
+    assert!(client.remove_submitter(&member));
+    assert!(!client.remove_submitter(&member));
+    assert!(!client.is_submitter_allowed(&member));
+
+    // Anyone may submit once the allowlist is switched off
+    client.set_allowlist_enabled(&false);
+    client.submit_encrypted_input(&member, &encrypted_data, &1, &epoch);
+    assert_eq!(client.batch_inputs(&0).len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #29)")]
+fn test_submitter_not_allowed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env));
+    let epoch = publish_encryption_key(&env, &client);
+
+    client.set_allowlist_enabled(&true);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x1234567890abcdef");
+    client.submit_encrypted_input(&submitter, &encrypted_data, &0, &epoch);
+}
+
+#[test]
+fn test_admin_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // The admin stays in place until the transfer is accepted
+    let pending = client.propose_admin(&new_admin);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(pending.admin, new_admin);
+    assert_eq!(client.pending_admin(), Some(pending));
+    assert_eq!(client.admin(), Some(admin.clone()));
+
+    assert_eq!(client.accept_admin(), new_admin);
+    assert_eq!(env.auths()[0].0, new_admin);
+    assert_eq!(client.admin(), Some(new_admin.clone()));
+    assert_eq!(client.pending_admin(), None);
+
+    // The new admin holds the admin rights
+    client.set_batch_limits(&10, &2);
+    assert_eq!(env.auths()[0].0, new_admin);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #102)")]
+fn test_admin_transfer_expired() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+    let pending = client.propose_admin(&Address::generate(&env));
+
+    // Keep the contract instance live past the expiry
+    env.ledger().with_mut(|li| li.max_entry_ttl = 20_000);
+    env.as_contract(&contract_id, || env.storage().instance().extend_ttl(20_000, 20_000));
+    env.ledger().with_mut(|li| li.sequence_number = pending.expires_at + 1);
+    client.accept_admin();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn test_renounce_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+    client.propose_admin(&Address::generate(&env));
+    client.renounce_admin();
+    assert_eq!(client.admin(), None);
+    assert_eq!(client.pending_admin(), None);
+
+    // The contract cannot be re-initialized once the admin is renounced
+    client.initialize(&Address::generate(&env));
+}
+
+#[test]
+fn test_submit_encrypted_inputs() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let first = String::from_str(&env, "0x1111111111");
+    client.submit_encrypted_input(&submitter, &first, &0, &epoch);
+
+    let encrypted_inputs = vec![
+        &env,
+        String::from_str(&env, "0x2222222222"),
+        String::from_str(&env, "0x3333333333"),
+        String::from_str(&env, "0x4444444444"),
+    ];
+    let indices = client.submit_encrypted_inputs(&submitter, &encrypted_inputs, &1, &epoch);
+    assert_eq!(indices, vec![&env, 1, 2, 3]);
+
+    // One authorization from the submitter covers the whole call
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, submitter.clone());
+
+    // Inputs are appended in order with consecutive nonces
+    let inputs = client.batch_inputs(&0);
+    assert_eq!(inputs.len(), 4);
+    for (index, input) in inputs.iter().enumerate().skip(1) {
+        assert_eq!(input.nonce, index as u64);
+        assert_eq!(input.encrypted_data, encrypted_inputs.get(index as u32 - 1).unwrap());
+    }
+    assert_eq!(client.submitter_nonce(&submitter), 4);
+    assert_eq!(client.submitter_input_count(&0, &submitter), 4);
+}
+
+#[test]
+fn test_submit_encrypted_inputs_is_atomic() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env));
+    let epoch = publish_encryption_key(&env, &client);
+
+    client.set_batch_limits(&2, &0);
+
+    let submitter = Address::generate(&env);
+    let encrypted_data = String::from_str(&env, "0x123456");
+
+    // Exceeding the batch capacity rejects every input of the call
+    let encrypted_inputs = vec![&env, encrypted_data.clone(), encrypted_data.clone(), encrypted_data.clone()];
+    assert!(client
+        .try_submit_encrypted_inputs(&submitter, &encrypted_inputs, &0, &epoch)
+        .is_err());
+
+    // So does a single empty input
+    let encrypted_inputs = vec![&env, encrypted_data.clone(), String::from_str(&env, "")];
+    assert!(client
+        .try_submit_encrypted_inputs(&submitter, &encrypted_inputs, &0, &epoch)
+        .is_err());
+
+    assert_eq!(client.batch_inputs(&0).len(), 0);
+    assert_eq!(client.submitter_nonce(&submitter), 0);
+}
+
+#[test]
+fn test_data_availability_mode() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env));
+    let epoch = publish_encryption_key(&env, &client);
+
+    assert!(!client.data_availability_mode());
+    client.set_data_availability_mode(&true);
+    assert!(client.data_availability_mode());
+
+    let submitter = Address::generate(&env);
+    let ciphertext = Bytes::from_array(&env, &[0xab; 64]);
+    let data_hash: BytesN<32> = env.crypto().sha256(&ciphertext).into();
+    let locator = String::from_str(&env, "");
+
+    let batch_id = client.submit_input_reference(&submitter, &data_hash, &64, &locator, &0, &epoch);
+    assert_eq!(batch_id, 0);
+
+    // Only the reference is stored on-chain
+    let input = client.batch_inputs(&0).get(0).unwrap();
+    assert!(input.encrypted_data.is_empty());
+    let data_ref = client.input_reference(&0, &0).unwrap();
+    assert_eq!(data_ref.data_hash, data_hash);
+    assert_eq!(data_ref.length, 64);
+    assert_eq!(data_ref.locator, locator);
+    assert_eq!(client.submitter_nonce(&submitter), 1);
+
+    // Referenced ciphertexts are capped in size
+    let too_large = 1_048_576 + 1;
+    let result =
+        client.try_submit_input_reference(&submitter, &data_hash, &too_large, &locator, &1, &epoch);
+    assert_eq!(result, Err(Ok(Error::InputReferenceTooLarge.into())));
+
+    // Ciphertexts are only looked up by hash, so locators are refused
+    let locator = String::from_str(&env, "https://example.com/data");
+    let result =
+        client.try_submit_input_reference(&submitter, &data_hash, &64, &locator, &1, &epoch);
+    assert_eq!(result, Err(Ok(Error::UnsupportedLocator.into())));
+
+    // Full ciphertexts are rejected while the mode is on
+    let encrypted_data = String::from_str(&env, "0x1234567890abcdef");
+    assert!(client
+        .try_submit_encrypted_input(&submitter, &encrypted_data, &1, &epoch)
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #31)")]
+fn test_input_reference_without_data_availability_mode() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(OnchainCommitment, ());
+    let client = OnchainCommitmentClient::new(&env, &contract_id);
+    let epoch = publish_encryption_key(&env, &client);
+
+    let submitter = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let locator = String::from_str(&env, "");
+    client.submit_input_reference(&submitter, &data_hash, &64, &locator, &0, &epoch);
+}
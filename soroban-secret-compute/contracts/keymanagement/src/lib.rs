@@ -1,11 +1,34 @@
 #![no_std]
 
+use shared::admin::{self, PendingAdmin};
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, BytesN, Env, IntoVal, Map,
-    Symbol, Val, Vec,
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    Bytes, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
 };
 
-const REGISTERED_TEES: Symbol = symbol_short!("TEES");
+const TEE_COUNT: Symbol = symbol_short!("TEECOUNT");
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const LIVENESS_WINDOW: Symbol = symbol_short!("LIVENESS");
+const COUNCIL: Symbol = symbol_short!("COUNCIL");
+const NEXT_PROPOSAL_ID: Symbol = symbol_short!("NEXTPROP");
+const MEASUREMENT_COUNT: Symbol = symbol_short!("MEASURES");
+const STAKE_CONFIG: Symbol = symbol_short!("STAKECFG");
+
+/// Default number of ledgers a TEE stays valid without a heartbeat (about one day)
+const DEFAULT_LIVENESS_WINDOW: u32 = 17_280;
+
+/// Maximum number of TEEs returned by one page of a listing
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Number of ledgers a council proposal stays open (about one day)
+pub const PROPOSAL_EXPIRY: u32 = 17_280;
+
+/// Number of ledgers (about 30 days) registry entries are kept alive after
+/// they are read or written
+pub const TEE_TTL_EXTEND: u32 = 518_400;
+
+/// Remaining TTL (about 7 days) below which an access extends a registry entry
+const TEE_TTL_THRESHOLD: u32 = 120_960;
 
 #[contract]
 pub struct TeeManagement;
@@ -15,6 +38,111 @@ pub struct TeeManagement;
 pub enum TeeStatus {
     Enabled,
     Disabled,
+    Revoked,
+}
+
+/// Bond TEE operators must keep staked in the Staking contract for each TEE
+/// they run
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StakeConfig {
+    pub staking_contract: Address,
+    pub min_bond: i128,
+}
+
+/// Audit record of a permanently revoked TEE
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revocation {
+    pub revoked_at: u32,
+    pub reason_code: u32,
+}
+
+/// Kind of enclave a TEE runs in
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PlatformType {
+    Sgx = 0,
+    Tdx = 1,
+    SevSnp = 2,
+    Nitro = 3,
+    Software = 4,
+}
+
+/// Remote-attestation report vouched for by a trusted verifier
+/// The verifier checks the platform evidence off-chain and signs the report
+/// so that the contract only has to verify one ed25519 signature
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestationReport {
+    pub tee_pubkey: BytesN<32>,
+    pub measurement: BytesN<32>,
+    pub platform: PlatformType,
+    pub expires_at: u32,
+}
+
+#[contracttype]
+pub enum DataKey {
+    Registrar(Address),
+    TrustedVerifier(BytesN<32>),
+    RotatedKey(BytesN<32>),
+    Tee(BytesN<32>),
+    TeeIndex(u32),
+    TeePosition(BytesN<32>),
+    Proposal(u64),
+    AllowedMeasurement(BytesN<32>),
+    Revocation(BytesN<32>),
+    OperatorTeeCount(Address),
+}
+
+/// Council of addresses that approve registry changes, M of N
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Council {
+    pub members: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// Arguments of register_tee for a council registration
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeeRegistration {
+    pub operator: Address,
+    pub report: AttestationReport,
+    pub verifier: BytesN<32>,
+    pub signature: BytesN<64>,
+    pub metadata: TeeMetadata,
+}
+
+/// Registry change the council can vote on
+// Contract types cannot be boxed, so the registration stays inline
+#[allow(clippy::large_enum_variant)]
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CouncilAction {
+    RegisterTee(TeeRegistration),
+    DisableTee(BytesN<32>),
+    EnableTee(BytesN<32>),
+    AllowMeasurement(BytesN<32>),
+    DisallowMeasurement(BytesN<32>),
+    SetCouncil(Council),
+    RevokeTee(BytesN<32>, u32),
+    AddTrustedVerifier(BytesN<32>),
+    RemoveTrustedVerifier(BytesN<32>),
+    SetLivenessWindow(u32),
+    SetStakeConfig(StakeConfig),
+}
+
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub action: CouncilAction,
+    pub approvals: Vec<Address>,
+    pub expires_at: u32,
+    pub executed: bool,
 }
 
 #[contracttype]
@@ -23,6 +151,24 @@ pub struct TeeInfo {
     pub pubkey: BytesN<32>,
     pub status: TeeStatus,
     pub registered_at: u64,
+    pub operator: Address,
+    pub endpoint: String,
+    pub measurement: BytesN<32>,
+    pub platform: PlatformType,
+    pub version: String,
+    pub capabilities: u64,
+    pub last_seen: u32,
+    pub valid_until: u32,
+}
+
+/// Operator-managed details of a TEE
+/// `capabilities` is a bitmap of the features the TEE supports
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeeMetadata {
+    pub endpoint: String,
+    pub version: String,
+    pub capabilities: u64,
 }
 
 #[contracterror]
@@ -32,104 +178,1032 @@ pub enum Error {
     TeeAlreadyRegistered = 1,
     TeeNotRegistered = 2,
     TeeDisabled = 3,
+    AlreadyInitialized = 4,
+    NotInitialized = 5,
+    Unauthorized = 6,
+    UntrustedVerifier = 7,
+    ReportExpired = 8,
+    KeyRotated = 9,
+    CouncilNotSet = 10,
+    InvalidCouncil = 11,
+    NotCouncilMember = 12,
+    ProposalNotFound = 13,
+    ProposalExpired = 14,
+    ProposalExecuted = 15,
+    AlreadyApproved = 16,
+    ThresholdNotMet = 17,
+    MeasurementNotAllowed = 18,
+    TeeRevoked = 19,
+    InsufficientStake = 20,
+    CouncilGoverned = 21,
+    InvalidStakeConfig = 22,
+    LastAllowedMeasurement = 23,
+}
+
+fn read_admin(env: &Env) -> Address {
+    match env.storage().instance().get(&ADMIN) {
+        Some(admin) => admin,
+        None => panic_with_error!(env, Error::NotInitialized),
+    }
+}
+
+fn require_admin(env: &Env) -> Address {
+    let admin = read_admin(env);
+    admin.require_auth();
+    admin
+}
+
+/// Require the admin while no council is set
+/// Once a council is set, configuration changes go through council proposals
+fn require_admin_before_council(env: &Env) {
+    require_admin(env);
+    if env.storage().instance().has(&COUNCIL) {
+        panic_with_error!(env, Error::CouncilGoverned);
+    }
+}
+
+/// Message a trusted verifier signs to vouch for a TEE:
+/// tee_pubkey (32 bytes) || measurement (32 bytes) || platform (4 bytes BE) ||
+/// expires_at (4 bytes BE)
+fn attestation_report_message(env: &Env, report: &AttestationReport) -> Bytes {
+    let mut message = Bytes::from_array(env, &report.tee_pubkey.to_array());
+    message.extend_from_array(&report.measurement.to_array());
+    message.extend_from_array(&(report.platform as u32).to_be_bytes());
+    message.extend_from_array(&report.expires_at.to_be_bytes());
+    message
+}
+
+/// Message a TEE signs with its key to report it is alive:
+/// tee_pubkey (32 bytes) || last_seen (4 bytes BE)
+/// Signing the previous heartbeat ledger keeps a heartbeat from being replayed
+fn heartbeat_message(env: &Env, tee_pubkey: &BytesN<32>, last_seen: u32) -> Bytes {
+    let mut message = Bytes::from_array(env, &tee_pubkey.to_array());
+    message.extend_from_array(&last_seen.to_be_bytes());
+    message
+}
+
+/// Message both keys sign to rotate a TEE key:
+/// old_pubkey (32 bytes) || new_pubkey (32 bytes)
+/// The old key is tombstoned by the rotation, so the message cannot be replayed
+fn key_rotation_message(env: &Env, old_pubkey: &BytesN<32>, new_pubkey: &BytesN<32>) -> Bytes {
+    let mut message = Bytes::from_array(env, &old_pubkey.to_array());
+    message.extend_from_array(&new_pubkey.to_array());
+    message
+}
+
+/// Check if a TEE has missed its heartbeats for longer than the liveness window
+fn is_stale(env: &Env, tee_info: &TeeInfo) -> bool {
+    let window = TeeManagement::liveness_window(env.clone());
+    window > 0 && env.ledger().sequence() > tee_info.last_seen.saturating_add(window)
+}
+
+/// Check a report was signed by a trusted verifier, has not expired and, once
+/// the measurement allowlist is populated, has an allowed measurement
+fn verify_attestation_report(
+    env: &Env,
+    report: &AttestationReport,
+    verifier: &BytesN<32>,
+    signature: &BytesN<64>,
+) {
+    if !TeeManagement::is_trusted_verifier(env.clone(), verifier.clone()) {
+        panic_with_error!(env, Error::UntrustedVerifier);
+    }
+    if report.expires_at <= env.ledger().sequence() {
+        panic_with_error!(env, Error::ReportExpired);
+    }
+    if TeeManagement::allowed_measurement_count(env.clone()) > 0
+        && !TeeManagement::is_measurement_allowed(env.clone(), report.measurement.clone())
+    {
+        panic_with_error!(env, Error::MeasurementNotAllowed);
+    }
+
+    let message = attestation_report_message(env, report);
+    env.crypto().ed25519_verify(verifier, &message, signature);
+}
+
+/// Require the caller to be the admin or a registrar
+/// Once a council is set, registry changes go through council proposals
+fn require_registrar(env: &Env, caller: &Address) {
+    let admin = read_admin(env);
+    caller.require_auth();
+    if env.storage().instance().has(&COUNCIL) {
+        panic_with_error!(env, Error::CouncilGoverned);
+    }
+
+    if *caller != admin && !TeeManagement::is_registrar(env.clone(), caller.clone()) {
+        panic_with_error!(env, Error::Unauthorized);
+    }
+}
+
+/// Check if an operator has at least the minimum bond staked for each of
+/// tee_count TEEs
+/// Always true while no stake configuration is set
+fn has_min_bond(env: &Env, operator: &Address, tee_count: u32) -> bool {
+    let config: StakeConfig = match env.storage().instance().get(&STAKE_CONFIG) {
+        Some(config) => config,
+        None => return true,
+    };
+
+    let args: Vec<Val> = (operator.clone(),).into_val(env);
+    let staked: i128 = env.invoke_contract(
+        &config.staking_contract,
+        &Symbol::new(env, "staked"),
+        args,
+    );
+    staked >= config.min_bond.saturating_mul(tee_count as i128)
+}
+
+/// Check and store the council members and threshold
+fn write_council(env: &Env, council: Council) -> bool {
+    if council.threshold == 0 || council.threshold > council.members.len() {
+        panic_with_error!(env, Error::InvalidCouncil);
+    }
+    for (i, member) in council.members.iter().enumerate() {
+        if council.members.first_index_of(&member) != Some(i as u32) {
+            panic_with_error!(env, Error::InvalidCouncil);
+        }
+    }
+
+    env.storage().instance().set(&COUNCIL, &council);
+    env.storage().instance().extend_ttl(100, 100);
+
+    // Emit event
+    let members_val: Val = council.members.into_val(env);
+    let threshold_val: Val = council.threshold.into_val(env);
+    env.events().publish(("COUNCIL_UPDATED",), (members_val, threshold_val));
+
+    true
+}
+
+fn read_council(env: &Env) -> Council {
+    match env.storage().instance().get(&COUNCIL) {
+        Some(council) => council,
+        None => panic_with_error!(env, Error::CouncilNotSet),
+    }
+}
+
+/// Require the caller to be a council member
+fn require_council_member(env: &Env, council: &Council, caller: &Address) {
+    caller.require_auth();
+
+    if !council.members.contains(caller) {
+        panic_with_error!(env, Error::NotCouncilMember);
+    }
+}
+
+fn read_proposal(env: &Env, proposal_id: u64) -> Proposal {
+    match env.storage().persistent().get(&DataKey::Proposal(proposal_id)) {
+        Some(proposal) => proposal,
+        None => panic_with_error!(env, Error::ProposalNotFound),
+    }
+}
+
+fn write_proposal(env: &Env, proposal: &Proposal) {
+    let key = DataKey::Proposal(proposal.id);
+    env.storage().persistent().set(&key, proposal);
+    env.storage().persistent().extend_ttl(&key, 100, 100);
+}
+
+/// Check a proposal can still be approved or executed
+fn require_open_proposal(env: &Env, proposal: &Proposal) {
+    if proposal.executed {
+        panic_with_error!(env, Error::ProposalExecuted);
+    }
+    if env.ledger().sequence() > proposal.expires_at {
+        panic_with_error!(env, Error::ProposalExpired);
+    }
+}
+
+/// Register a TEE once the caller's authority has been checked
+fn register(
+    env: &Env,
+    operator: Address,
+    report: AttestationReport,
+    verifier: BytesN<32>,
+    signature: BytesN<64>,
+    metadata: TeeMetadata,
+) -> bool {
+    verify_attestation_report(env, &report, &verifier, &signature);
+    let tee_pubkey = report.tee_pubkey;
+    if env.storage().persistent().has(&DataKey::RotatedKey(tee_pubkey.clone())) {
+        panic_with_error!(env, Error::KeyRotated);
+    }
+    if env.storage().persistent().has(&DataKey::Revocation(tee_pubkey.clone())) {
+        panic_with_error!(env, Error::TeeRevoked);
+    }
+
+    // Check if TEE is already registered
+    if env.storage().persistent().has(&DataKey::Tee(tee_pubkey.clone())) {
+        return false;
+    }
+    let tee_count = TeeManagement::operator_tee_count(env.clone(), operator.clone());
+    if !has_min_bond(env, &operator, tee_count + 1) {
+        panic_with_error!(env, Error::InsufficientStake);
+    }
+
+    // Register the TEE with enabled status
+    let tee_info = TeeInfo {
+        pubkey: tee_pubkey.clone(),
+        status: TeeStatus::Enabled,
+        registered_at: env.ledger().timestamp(),
+        last_seen: env.ledger().sequence(),
+        operator,
+        endpoint: metadata.endpoint,
+        measurement: report.measurement.clone(),
+        platform: report.platform,
+        version: metadata.version,
+        capabilities: metadata.capabilities,
+        valid_until: report.expires_at,
+    };
+    add_tee(env, &tee_info);
+
+    // Emit event
+    let tee_pubkey_val: Val = tee_pubkey.into_val(env);
+    let measurement_val: Val = report.measurement.into_val(env);
+    env.events().publish(("TEE_REGISTERED",), (tee_pubkey_val, measurement_val));
+
+    true
+}
+
+/// Enable or disable a TEE once the caller's authority has been checked
+/// Revoked TEEs keep their status
+fn set_tee_status(env: &Env, tee_pubkey: BytesN<32>, status: TeeStatus) -> bool {
+    // Check if TEE exists
+    if let Some(mut tee_info) = read_tee(env, &tee_pubkey) {
+        require_not_revoked(env, &tee_info);
+        tee_info.status = status.clone();
+        write_tee(env, &tee_info);
+
+        // Emit event
+        let tee_pubkey_val: Val = tee_pubkey.into_val(env);
+        if status == TeeStatus::Enabled {
+            env.events().publish(("TEE_ENABLED",), (tee_pubkey_val,));
+        } else {
+            env.events().publish(("TEE_DISABLED",), (tee_pubkey_val,));
+        }
+
+        true
+    } else {
+        false
+    }
+}
+
+/// Add or remove a measurement from the allowlist
+/// The last measurement cannot be removed, since an empty allowlist accepts
+/// any measurement; allow its replacement first
+fn set_measurement_allowed(env: &Env, measurement: BytesN<32>, allowed: bool) -> bool {
+    let key = DataKey::AllowedMeasurement(measurement.clone());
+    if env.storage().persistent().has(&key) == allowed {
+        return false;
+    }
+
+    let count = TeeManagement::allowed_measurement_count(env.clone());
+    if !allowed && count == 1 {
+        panic_with_error!(env, Error::LastAllowedMeasurement);
+    }
+
+    let measurement_val: Val = measurement.into_val(env);
+    if allowed {
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+        env.storage().instance().set(&MEASUREMENT_COUNT, &(count + 1));
+        env.events().publish(("MEASUREMENT_ALLOWED",), (measurement_val,));
+    } else {
+        env.storage().persistent().remove(&key);
+        env.storage().instance().set(&MEASUREMENT_COUNT, &(count - 1));
+        env.events().publish(("MEASUREMENT_DISALLOWED",), (measurement_val,));
+    }
+    env.storage().instance().extend_ttl(100, 100);
+
+    true
+}
+
+/// Trust or stop trusting a verifier key
+fn set_verifier_trusted(env: &Env, verifier: BytesN<32>, trusted: bool) -> bool {
+    let key = DataKey::TrustedVerifier(verifier.clone());
+    if env.storage().persistent().has(&key) == trusted {
+        return false;
+    }
+
+    let verifier_val: Val = verifier.into_val(env);
+    if trusted {
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+        env.events().publish(("VERIFIER_ADDED",), (verifier_val,));
+    } else {
+        env.storage().persistent().remove(&key);
+        env.events().publish(("VERIFIER_REMOVED",), (verifier_val,));
+    }
+
+    true
+}
+
+fn write_liveness_window(env: &Env, window: u32) -> bool {
+    env.storage().instance().set(&LIVENESS_WINDOW, &window);
+    env.storage().instance().extend_ttl(100, 100);
+
+    true
+}
+
+/// Check and store the stake configuration
+fn write_stake_config(env: &Env, config: StakeConfig) -> bool {
+    if config.min_bond <= 0 {
+        panic_with_error!(env, Error::InvalidStakeConfig);
+    }
+
+    env.storage().instance().set(&STAKE_CONFIG, &config);
+    env.storage().instance().extend_ttl(100, 100);
+
+    true
+}
+
+/// Permanently revoke a TEE once the caller's authority has been checked
+fn revoke(env: &Env, tee_pubkey: BytesN<32>, reason_code: u32) -> bool {
+    let mut tee_info = match read_tee(env, &tee_pubkey) {
+        Some(tee_info) => tee_info,
+        None => return false,
+    };
+    if tee_info.status == TeeStatus::Revoked {
+        return false;
+    }
+
+    tee_info.status = TeeStatus::Revoked;
+    write_tee(env, &tee_info);
+    update_operator_tee_count(env, &tee_info.operator, -1);
+
+    let revocation = Revocation {
+        revoked_at: env.ledger().sequence(),
+        reason_code,
+    };
+    let key = DataKey::Revocation(tee_pubkey.clone());
+    env.storage().persistent().set(&key, &revocation);
+    env.storage().persistent().extend_ttl(&key, 100, 100);
+
+    // Emit event
+    let tee_pubkey_val: Val = tee_pubkey.into_val(env);
+    let reason_code_val: Val = reason_code.into_val(env);
+    let revoked_at_val: Val = revocation.revoked_at.into_val(env);
+    env.events().publish(
+        ("TEE_REVOKED",),
+        (tee_pubkey_val, reason_code_val, revoked_at_val),
+    );
+
+    true
+}
+
+fn require_not_revoked(env: &Env, tee_info: &TeeInfo) {
+    if tee_info.status == TeeStatus::Revoked {
+        panic_with_error!(env, Error::TeeRevoked);
+    }
+}
+
+/// Keep a registry entry alive for TEE_TTL_EXTEND ledgers once its TTL drops
+/// below TEE_TTL_THRESHOLD
+fn extend_tee_ttl(env: &Env, key: &DataKey) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TEE_TTL_THRESHOLD, TEE_TTL_EXTEND);
+}
+
+fn read_tee(env: &Env, tee_pubkey: &BytesN<32>) -> Option<TeeInfo> {
+    let key = DataKey::Tee(tee_pubkey.clone());
+    let tee_info = env.storage().persistent().get(&key);
+    if tee_info.is_some() {
+        extend_tee_ttl(env, &key);
+    }
+    tee_info
+}
+
+fn write_tee(env: &Env, tee_info: &TeeInfo) {
+    let key = DataKey::Tee(tee_info.pubkey.clone());
+    env.storage().persistent().set(&key, tee_info);
+    extend_tee_ttl(env, &key);
+}
+
+/// Read the key at a position of the registry index
+fn read_tee_index(env: &Env, position: u32) -> BytesN<32> {
+    let key = DataKey::TeeIndex(position);
+    let tee_pubkey = env.storage().persistent().get(&key).unwrap();
+    extend_tee_ttl(env, &key);
+    tee_pubkey
+}
+
+/// Add delta to the number of unrevoked TEEs run by an operator
+fn update_operator_tee_count(env: &Env, operator: &Address, delta: i32) {
+    let count = TeeManagement::operator_tee_count(env.clone(), operator.clone())
+        .checked_add_signed(delta)
+        .unwrap();
+    let key = DataKey::OperatorTeeCount(operator.clone());
+    if count == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &count);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+    }
+}
+
+/// Store a new TEE and append it to the registry index
+fn add_tee(env: &Env, tee_info: &TeeInfo) {
+    let count = TeeManagement::tee_count(env.clone());
+
+    write_tee(env, tee_info);
+    let index_key = DataKey::TeeIndex(count);
+    env.storage().persistent().set(&index_key, &tee_info.pubkey);
+    extend_tee_ttl(env, &index_key);
+    let position_key = DataKey::TeePosition(tee_info.pubkey.clone());
+    env.storage().persistent().set(&position_key, &count);
+    extend_tee_ttl(env, &position_key);
+
+    env.storage().instance().set(&TEE_COUNT, &(count + 1));
+    env.storage().instance().extend_ttl(100, 100);
+    update_operator_tee_count(env, &tee_info.operator, 1);
+}
+
+/// Remove a TEE, moving the last TEE of the index into its position
+fn remove_tee(env: &Env, tee_pubkey: &BytesN<32>) {
+    let position_key = DataKey::TeePosition(tee_pubkey.clone());
+    let position: u32 = match env.storage().persistent().get(&position_key) {
+        Some(position) => position,
+        None => return,
+    };
+    let last = TeeManagement::tee_count(env.clone()) - 1;
+    if let Some(tee_info) = read_tee(env, tee_pubkey) {
+        if tee_info.status != TeeStatus::Revoked {
+            update_operator_tee_count(env, &tee_info.operator, -1);
+        }
+    }
+
+    if position != last {
+        let last_pubkey = read_tee_index(env, last);
+        let index_key = DataKey::TeeIndex(position);
+        env.storage().persistent().set(&index_key, &last_pubkey);
+        extend_tee_ttl(env, &index_key);
+        let last_position_key = DataKey::TeePosition(last_pubkey);
+        env.storage().persistent().set(&last_position_key, &position);
+        extend_tee_ttl(env, &last_position_key);
+    }
+    env.storage().persistent().remove(&DataKey::TeeIndex(last));
+    env.storage().persistent().remove(&position_key);
+    env.storage().persistent().remove(&DataKey::Tee(tee_pubkey.clone()));
+
+    env.storage().instance().set(&TEE_COUNT, &last);
+    env.storage().instance().extend_ttl(100, 100);
+}
+
+/// Get the TEEs at positions start..start + limit of the registry index
+fn tee_page(env: &Env, start: u32, limit: u32) -> Vec<TeeInfo> {
+    let end = start
+        .saturating_add(limit.min(MAX_PAGE_SIZE))
+        .min(TeeManagement::tee_count(env.clone()));
+
+    let mut tees = Vec::<TeeInfo>::new(env);
+    for position in start..end {
+        let tee_pubkey = read_tee_index(env, position);
+        if let Some(tee_info) = read_tee(env, &tee_pubkey) {
+            tees.push_back(tee_info);
+        }
+    }
+
+    tees
 }
 
 #[contractimpl]
 impl TeeManagement {
-    /// Register a TEE with its public key
-    /// Returns true if registration was successful, false if already registered
-    pub fn register_tee(env: Env, tee_pubkey: BytesN<32>) -> bool {
-        let mut tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
-            .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
+    /// Set the contract admin, can only be called once
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().instance().has(&ADMIN) || admin::admin_renounced(&env) {
+            panic_with_error!(&env, Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().extend_ttl(100, 100);
+    }
+
+    /// Get the contract admin
+    pub fn admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ADMIN)
+    }
 
-        // Check if TEE is already registered
-        if tees.contains_key(tee_pubkey.clone()) {
+    /// Get the admin transfer waiting to be accepted
+    pub fn pending_admin(env: Env) -> Option<PendingAdmin> {
+        admin::pending_admin(&env)
+    }
+
+    /// Propose a new admin, who must accept before the proposal expires
+    /// Only callable by the admin
+    pub fn propose_admin(env: Env, new_admin: Address) -> PendingAdmin {
+        admin::propose_admin(&env, &ADMIN, new_admin)
+    }
+
+    /// Accept a pending admin transfer
+    /// Only callable by the proposed admin
+    pub fn accept_admin(env: Env) -> Address {
+        admin::accept_admin(&env, &ADMIN)
+    }
+
+    /// Give up the admin role permanently
+    /// Only callable by the admin
+    pub fn renounce_admin(env: Env) {
+        admin::renounce_admin(&env, &ADMIN)
+    }
+
+    /// Check if an address may manage the registry on behalf of the admin
+    pub fn is_registrar(env: Env, registrar: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Registrar(registrar))
+            .unwrap_or(false)
+    }
+
+    /// Grant the registrar role
+    /// Only callable by the admin
+    pub fn add_registrar(env: Env, registrar: Address) -> bool {
+        require_admin(&env);
+
+        let key = DataKey::Registrar(registrar.clone());
+        if env.storage().persistent().has(&key) {
             return false;
         }
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        // Emit event
+        let registrar_val: Val = registrar.into_val(&env);
+        env.events().publish(("REGISTRAR_ADDED",), (registrar_val,));
+
+        true
+    }
 
-        // Register the TEE with enabled status
-        let tee_info = TeeInfo {
-            pubkey: tee_pubkey.clone(),
-            status: TeeStatus::Enabled,
-            registered_at: env.ledger().timestamp(),
+    /// Revoke the registrar role
+    /// Only callable by the admin
+    pub fn remove_registrar(env: Env, registrar: Address) -> bool {
+        require_admin(&env);
+
+        let key = DataKey::Registrar(registrar.clone());
+        if !env.storage().persistent().has(&key) {
+            return false;
+        }
+        env.storage().persistent().remove(&key);
+
+        // Emit event
+        let registrar_val: Val = registrar.into_val(&env);
+        env.events().publish(("REGISTRAR_REMOVED",), (registrar_val,));
+
+        true
+    }
+
+    /// Check if attestation reports signed by a verifier key are accepted
+    pub fn is_trusted_verifier(env: Env, verifier: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TrustedVerifier(verifier))
+            .unwrap_or(false)
+    }
+
+    /// Trust attestation reports signed by a verifier key
+    /// Only callable by the admin while no council is set
+    pub fn add_trusted_verifier(env: Env, verifier: BytesN<32>) -> bool {
+        require_admin_before_council(&env);
+
+        set_verifier_trusted(&env, verifier, true)
+    }
+
+    /// Stop trusting a verifier key
+    /// Only callable by the admin while no council is set
+    pub fn remove_trusted_verifier(env: Env, verifier: BytesN<32>) -> bool {
+        require_admin_before_council(&env);
+
+        set_verifier_trusted(&env, verifier, false)
+    }
+
+    /// Register a TEE from an attestation report signed by a trusted verifier
+    /// Only callable by the admin or a registrar while no council is set, with
+    /// the consent of the operator that controls the TEE
+    /// Returns true if registration was successful, false if already registered
+    pub fn register_tee(
+        env: Env,
+        caller: Address,
+        operator: Address,
+        report: AttestationReport,
+        verifier: BytesN<32>,
+        signature: BytesN<64>,
+        metadata: TeeMetadata,
+    ) -> bool {
+        require_registrar(&env, &caller);
+        if operator != caller {
+            operator.require_auth();
+        }
+
+        register(&env, operator, report, verifier, signature, metadata)
+    }
+
+    /// Renew the registration of a TEE from a fresh attestation report signed
+    /// by a trusted verifier, updating its measurement, platform and expiry
+    /// Only callable by the TEE operator
+    pub fn renew_tee(
+        env: Env,
+        report: AttestationReport,
+        verifier: BytesN<32>,
+        signature: BytesN<64>,
+    ) {
+        let tee_pubkey = report.tee_pubkey.clone();
+        let mut tee_info = match read_tee(&env, &tee_pubkey) {
+            Some(tee_info) => tee_info,
+            None => panic_with_error!(&env, Error::TeeNotRegistered),
         };
+        require_not_revoked(&env, &tee_info);
+        tee_info.operator.require_auth();
+        verify_attestation_report(&env, &report, &verifier, &signature);
 
-        tees.set(tee_pubkey.clone(), tee_info);
-        env.storage().instance().set(&REGISTERED_TEES, &tees);
-        env.storage().instance().extend_ttl(100, 100);
+        tee_info.measurement = report.measurement.clone();
+        tee_info.platform = report.platform;
+        tee_info.valid_until = report.expires_at;
+        write_tee(&env, &tee_info);
 
         // Emit event
         let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
-        env.events().publish(("TEE_REGISTERED",), (tee_pubkey_val,));
+        let measurement_val: Val = report.measurement.into_val(&env);
+        let valid_until_val: Val = report.expires_at.into_val(&env);
+        env.events().publish(
+            ("TEE_RENEWED",),
+            (tee_pubkey_val, measurement_val, valid_until_val),
+        );
+    }
 
-        true
+    /// Update the endpoint, software version and capabilities of a TEE
+    /// Only callable by the TEE operator
+    pub fn update_tee_metadata(env: Env, tee_pubkey: BytesN<32>, metadata: TeeMetadata) {
+        let mut tee_info = match read_tee(&env, &tee_pubkey) {
+            Some(tee_info) => tee_info,
+            None => panic_with_error!(&env, Error::TeeNotRegistered),
+        };
+        tee_info.operator.require_auth();
+
+        tee_info.endpoint = metadata.endpoint.clone();
+        tee_info.version = metadata.version.clone();
+        tee_info.capabilities = metadata.capabilities;
+        write_tee(&env, &tee_info);
+
+        // Emit event
+        let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
+        let metadata_val: Val = metadata.into_val(&env);
+        env.events().publish(("TEE_METADATA_UPDATED",), (tee_pubkey_val, metadata_val));
     }
 
-    /// Disable a TEE (marks it as disabled but keeps it in the registry)
-    pub fn disable_tee(env: Env, tee_pubkey: BytesN<32>) -> bool {
-        let mut tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
+    /// Move a TEE registration, with its operator, metadata and liveness, to a
+    /// new key and tombstone the old key so it can never be registered again
+    /// The new key is the subject of a fresh attestation report signed by a
+    /// trusted verifier, which sets its measurement, platform and expiry
+    /// Both keys must sign old_pubkey || new_pubkey and the operator must
+    /// authorize the rotation
+    pub fn rotate_tee_key(
+        env: Env,
+        old_pubkey: BytesN<32>,
+        report: AttestationReport,
+        verifier: BytesN<32>,
+        report_signature: BytesN<64>,
+        sig_by_old: BytesN<64>,
+        sig_by_new: BytesN<64>,
+    ) {
+        let new_pubkey = report.tee_pubkey.clone();
+        let mut tee_info = match read_tee(&env, &old_pubkey) {
+            Some(tee_info) => tee_info,
+            None => panic_with_error!(&env, Error::TeeNotRegistered),
+        };
+        if env.storage().persistent().has(&DataKey::Tee(new_pubkey.clone())) {
+            panic_with_error!(&env, Error::TeeAlreadyRegistered);
+        }
+        if env.storage().persistent().has(&DataKey::RotatedKey(new_pubkey.clone())) {
+            panic_with_error!(&env, Error::KeyRotated);
+        }
+        require_not_revoked(&env, &tee_info);
+        tee_info.operator.require_auth();
+
+        let message = key_rotation_message(&env, &old_pubkey, &new_pubkey);
+        env.crypto().ed25519_verify(&old_pubkey, &message, &sig_by_old);
+        env.crypto().ed25519_verify(&new_pubkey, &message, &sig_by_new);
+        verify_attestation_report(&env, &report, &verifier, &report_signature);
+
+        tee_info.pubkey = new_pubkey.clone();
+        tee_info.measurement = report.measurement;
+        tee_info.platform = report.platform;
+        tee_info.valid_until = report.expires_at;
+        remove_tee(&env, &old_pubkey);
+        add_tee(&env, &tee_info);
+
+        let key = DataKey::RotatedKey(old_pubkey.clone());
+        env.storage().persistent().set(&key, &new_pubkey);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        // Emit event
+        let old_pubkey_val: Val = old_pubkey.into_val(&env);
+        let new_pubkey_val: Val = new_pubkey.into_val(&env);
+        env.events().publish(("TEE_KEY_ROTATED",), (old_pubkey_val, new_pubkey_val));
+    }
+
+    /// Get the key a rotated TEE key was replaced by
+    pub fn rotated_key(env: Env, old_pubkey: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RotatedKey(old_pubkey))
+    }
+
+    /// Get the number of ledgers a TEE stays valid without a heartbeat
+    pub fn liveness_window(env: Env) -> u32 {
+        env.storage()
             .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
-
-        // Check if TEE exists
-        if let Some(mut tee_info) = tees.get(tee_pubkey.clone()) {
-            // Update status to disabled
-            tee_info.status = TeeStatus::Disabled;
-            tees.set(tee_pubkey.clone(), tee_info);
-            env.storage().instance().set(&REGISTERED_TEES, &tees);
-            env.storage().instance().extend_ttl(100, 100);
-
-            // Emit event
-            let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
-            env.events().publish(("TEE_DISABLED",), (tee_pubkey_val,));
+            .get(&LIVENESS_WINDOW)
+            .unwrap_or(DEFAULT_LIVENESS_WINDOW)
+    }
 
-            true
-        } else {
-            false
+    /// Set the liveness window, 0 disables liveness checks
+    /// Only callable by the admin while no council is set
+    pub fn set_liveness_window(env: Env, window: u32) {
+        require_admin_before_council(&env);
+
+        write_liveness_window(&env, window);
+    }
+
+    /// Record that a TEE is alive
+    /// The signature must be made by tee_pubkey over tee_pubkey || last_seen,
+    /// where last_seen is the ledger of its previous heartbeat
+    pub fn heartbeat(env: Env, tee_pubkey: BytesN<32>, signature: BytesN<64>) {
+        let mut tee_info = match read_tee(&env, &tee_pubkey) {
+            Some(tee_info) => tee_info,
+            None => panic_with_error!(&env, Error::TeeNotRegistered),
+        };
+
+        let message = heartbeat_message(&env, &tee_pubkey, tee_info.last_seen);
+        env.crypto().ed25519_verify(&tee_pubkey, &message, &signature);
+
+        tee_info.last_seen = env.ledger().sequence();
+        write_tee(&env, &tee_info);
+
+        // Emit event
+        let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
+        let last_seen_val: Val = env.ledger().sequence().into_val(&env);
+        env.events().publish(("TEE_HEARTBEAT",), (tee_pubkey_val, last_seen_val));
+    }
+
+    /// Get the enabled TEEs that missed their heartbeats for longer than the
+    /// liveness window among the page list_tees(start, limit) would return
+    pub fn get_stale_tees(env: Env, start: u32, limit: u32) -> Vec<TeeInfo> {
+        let mut stale_tees = Vec::<TeeInfo>::new(&env);
+        for tee_info in tee_page(&env, start, limit).iter() {
+            if tee_info.status == TeeStatus::Enabled && is_stale(&env, &tee_info) {
+                stale_tees.push_back(tee_info);
+            }
         }
+
+        stale_tees
     }
 
-    /// Enable a previously disabled TEE
-    pub fn enable_tee(env: Env, tee_pubkey: BytesN<32>) -> bool {
-        let mut tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
-            .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
-
-        // Check if TEE exists
-        if let Some(mut tee_info) = tees.get(tee_pubkey.clone()) {
-            // Update status to enabled
-            tee_info.status = TeeStatus::Enabled;
-            tees.set(tee_pubkey.clone(), tee_info);
-            env.storage().instance().set(&REGISTERED_TEES, &tees);
-            env.storage().instance().extend_ttl(100, 100);
-
-            // Emit event
-            let tee_pubkey_val: Val = tee_pubkey.into_val(&env);
-            env.events().publish(("TEE_ENABLED",), (tee_pubkey_val,));
+    /// Get the governance council
+    pub fn council(env: Env) -> Option<Council> {
+        env.storage().instance().get(&COUNCIL)
+    }
 
-            true
-        } else {
-            false
+    /// Set the first council members and the number of approvals a proposal
+    /// needs, handing registry changes over to the council
+    /// Only callable by the admin while no council is set; the council then
+    /// changes itself with a SetCouncil proposal
+    pub fn set_council(env: Env, members: Vec<Address>, threshold: u32) {
+        require_admin(&env);
+        if env.storage().instance().has(&COUNCIL) {
+            panic_with_error!(&env, Error::CouncilGoverned);
         }
+
+        write_council(&env, Council { members, threshold });
     }
 
-    /// Check if a TEE is valid (registered AND enabled)
-    pub fn is_valid_tee(env: Env, tee_pubkey: BytesN<32>) -> bool {
-        let tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
-            .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
+    /// Get a council proposal
+    pub fn proposal(env: Env, proposal_id: u64) -> Option<Proposal> {
+        env.storage().persistent().get(&DataKey::Proposal(proposal_id))
+    }
 
-        if let Some(tee_info) = tees.get(tee_pubkey) {
+    /// Propose a registry change, counting as the proposer's approval
+    /// Only callable by a council member; registrations also need the
+    /// consent of the operator
+    pub fn propose_action(env: Env, proposer: Address, action: CouncilAction) -> u64 {
+        let council = read_council(&env);
+        require_council_member(&env, &council, &proposer);
+        if let CouncilAction::RegisterTee(registration) = &action {
+            if registration.operator != proposer {
+                registration.operator.require_auth();
+            }
+        }
+
+        let proposal_id: u64 = env.storage().instance().get(&NEXT_PROPOSAL_ID).unwrap_or(0);
+        env.storage().instance().set(&NEXT_PROPOSAL_ID, &(proposal_id + 1));
+        env.storage().instance().extend_ttl(100, 100);
+
+        let proposal = Proposal {
+            id: proposal_id,
+            proposer: proposer.clone(),
+            action,
+            approvals: Vec::from_array(&env, [proposer.clone()]),
+            expires_at: env.ledger().sequence() + PROPOSAL_EXPIRY,
+            executed: false,
+        };
+        write_proposal(&env, &proposal);
+
+        // Emit event
+        let proposal_id_val: Val = proposal_id.into_val(&env);
+        let proposer_val: Val = proposer.into_val(&env);
+        let action_val: Val = proposal.action.into_val(&env);
+        let expires_at_val: Val = proposal.expires_at.into_val(&env);
+        env.events().publish(
+            ("PROPOSAL_CREATED",),
+            (proposal_id_val, proposer_val, action_val, expires_at_val),
+        );
+
+        proposal_id
+    }
+
+    /// Approve an open proposal
+    /// Only callable by a council member, once per proposal
+    /// Returns the number of approvals
+    pub fn approve_action(env: Env, approver: Address, proposal_id: u64) -> u32 {
+        let council = read_council(&env);
+        require_council_member(&env, &council, &approver);
+
+        let mut proposal = read_proposal(&env, proposal_id);
+        require_open_proposal(&env, &proposal);
+        if proposal.approvals.contains(&approver) {
+            panic_with_error!(&env, Error::AlreadyApproved);
+        }
+
+        proposal.approvals.push_back(approver.clone());
+        write_proposal(&env, &proposal);
+
+        // Emit event
+        let approvals = proposal.approvals.len();
+        let proposal_id_val: Val = proposal_id.into_val(&env);
+        let approver_val: Val = approver.into_val(&env);
+        let approvals_val: Val = approvals.into_val(&env);
+        env.events().publish(
+            ("PROPOSAL_APPROVED",),
+            (proposal_id_val, approver_val, approvals_val),
+        );
+
+        approvals
+    }
+
+    /// Execute a proposal approved by at least threshold current council
+    /// members; callable by anyone once the threshold is met
+    /// Returns the result of the registry change
+    pub fn execute_action(env: Env, proposal_id: u64) -> bool {
+        let council = read_council(&env);
+        let mut proposal = read_proposal(&env, proposal_id);
+        require_open_proposal(&env, &proposal);
+
+        // Approvals of members that left the council no longer count
+        let mut approvals = 0;
+        for approver in proposal.approvals.iter() {
+            if council.members.contains(&approver) {
+                approvals += 1;
+            }
+        }
+        if approvals < council.threshold {
+            panic_with_error!(&env, Error::ThresholdNotMet);
+        }
+
+        proposal.executed = true;
+        write_proposal(&env, &proposal);
+
+        let result = match proposal.action {
+            CouncilAction::RegisterTee(registration) => register(
+                &env,
+                registration.operator,
+                registration.report,
+                registration.verifier,
+                registration.signature,
+                registration.metadata,
+            ),
+            CouncilAction::DisableTee(tee_pubkey) => {
+                set_tee_status(&env, tee_pubkey, TeeStatus::Disabled)
+            }
+            CouncilAction::EnableTee(tee_pubkey) => {
+                set_tee_status(&env, tee_pubkey, TeeStatus::Enabled)
+            }
+            CouncilAction::AllowMeasurement(measurement) => {
+                set_measurement_allowed(&env, measurement, true)
+            }
+            CouncilAction::DisallowMeasurement(measurement) => {
+                set_measurement_allowed(&env, measurement, false)
+            }
+            CouncilAction::SetCouncil(council) => write_council(&env, council),
+            CouncilAction::RevokeTee(tee_pubkey, reason_code) => {
+                revoke(&env, tee_pubkey, reason_code)
+            }
+            CouncilAction::AddTrustedVerifier(verifier) => {
+                set_verifier_trusted(&env, verifier, true)
+            }
+            CouncilAction::RemoveTrustedVerifier(verifier) => {
+                set_verifier_trusted(&env, verifier, false)
+            }
+            CouncilAction::SetLivenessWindow(window) => write_liveness_window(&env, window),
+            CouncilAction::SetStakeConfig(config) => write_stake_config(&env, config),
+        };
+
+        // Emit event
+        let proposal_id_val: Val = proposal_id.into_val(&env);
+        let result_val: Val = result.into_val(&env);
+        env.events().publish(("PROPOSAL_EXECUTED",), (proposal_id_val, result_val));
+
+        result
+    }
+
+    /// Check if a measurement is on the allowlist
+    pub fn is_measurement_allowed(env: Env, measurement: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AllowedMeasurement(measurement))
+            .unwrap_or(false)
+    }
+
+    /// Get the number of allowlisted measurements
+    /// Until the first measurement is allowed, reports with any measurement are
+    /// accepted; once populated the allowlist can never be emptied again
+    pub fn allowed_measurement_count(env: Env) -> u32 {
+        env.storage().instance().get(&MEASUREMENT_COUNT).unwrap_or(0)
+    }
+
+    /// Disable a TEE (marks it as disabled but keeps it in the registry)
+    /// Only callable by the admin or a registrar while no council is set
+    pub fn disable_tee(env: Env, caller: Address, tee_pubkey: BytesN<32>) -> bool {
+        require_registrar(&env, &caller);
+
+        set_tee_status(&env, tee_pubkey, TeeStatus::Disabled)
+    }
+
+    /// Enable a previously disabled TEE
+    /// Only callable by the admin or a registrar while no council is set
+    pub fn enable_tee(env: Env, caller: Address, tee_pubkey: BytesN<32>) -> bool {
+        require_registrar(&env, &caller);
+
+        set_tee_status(&env, tee_pubkey, TeeStatus::Enabled)
+    }
+
+    /// Get the stake configuration
+    pub fn stake_config(env: Env) -> Option<StakeConfig> {
+        env.storage().instance().get(&STAKE_CONFIG)
+    }
+
+    /// Require TEE operators to keep at least min_bond staked in the Staking
+    /// contract for each TEE they run, checked at registration and by
+    /// is_valid_tee
+    /// Only callable by the admin while no council is set, min_bond must be
+    /// positive
+    pub fn set_stake_config(env: Env, staking_contract: Address, min_bond: i128) {
+        require_admin_before_council(&env);
+
+        let config = StakeConfig {
+            staking_contract,
+            min_bond,
+        };
+        write_stake_config(&env, config);
+    }
+
+    /// Permanently revoke a compromised TEE, it can never be enabled or
+    /// registered again
+    /// Only callable by the admin or a registrar while no council is set
+    /// Returns false if the TEE is not registered or already revoked
+    pub fn revoke_tee(env: Env, caller: Address, tee_pubkey: BytesN<32>, reason_code: u32) -> bool {
+        require_registrar(&env, &caller);
+
+        revoke(&env, tee_pubkey, reason_code)
+    }
+
+    /// Get the number of unrevoked TEEs run by an operator, each of which
+    /// needs min_bond staked
+    pub fn operator_tee_count(env: Env, operator: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OperatorTeeCount(operator))
+            .unwrap_or(0)
+    }
+
+    /// Get the revocation record of a TEE
+    pub fn revocation(env: Env, tee_pubkey: BytesN<32>) -> Option<Revocation> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Revocation(tee_pubkey))
+    }
+
+    /// Check if a TEE is valid (registered, enabled, alive, with an unexpired
+    /// registration and an operator that keeps the minimum bond staked for
+    /// each of its TEEs)
+    pub fn is_valid_tee(env: Env, tee_pubkey: BytesN<32>) -> bool {
+        if let Some(tee_info) = read_tee(&env, &tee_pubkey) {
             tee_info.status == TeeStatus::Enabled
+                && env.ledger().sequence() < tee_info.valid_until
+                && !is_stale(&env, &tee_info)
+                && has_min_bond(
+                    &env,
+                    &tee_info.operator,
+                    Self::operator_tee_count(env.clone(), tee_info.operator.clone()),
+                )
         } else {
             false
         }
@@ -137,41 +1211,52 @@ impl TeeManagement {
 
     /// Check if a TEE is registered (regardless of status)
     pub fn is_tee_registered(env: Env, tee_pubkey: BytesN<32>) -> bool {
-        let tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
-            .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
-
-        tees.contains_key(tee_pubkey)
+        env.storage().persistent().has(&DataKey::Tee(tee_pubkey))
     }
 
     /// Get TEE information
     pub fn get_tee_info(env: Env, tee_pubkey: BytesN<32>) -> Option<TeeInfo> {
-        let tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
-            .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
-
-        tees.get(tee_pubkey)
+        read_tee(&env, &tee_pubkey)
     }
 
     /// Get all registered TEEs
+    /// Reads the whole registry, use list_tees for large registries
     pub fn get_all_tees(env: Env) -> Vec<TeeInfo> {
-        let tees: Map<BytesN<32>, TeeInfo> = env
-            .storage()
-            .instance()
-            .get(&REGISTERED_TEES)
-            .unwrap_or_else(|| Map::new(&env));
+        let count = Self::tee_count(env.clone());
 
         let mut all_tees = Vec::<TeeInfo>::new(&env);
-        for (_, tee_info) in tees.iter() {
-            all_tees.push_back(tee_info);
+        let mut start = 0;
+        while start < count {
+            all_tees.append(&tee_page(&env, start, MAX_PAGE_SIZE));
+            start += MAX_PAGE_SIZE;
         }
 
         all_tees
     }
+
+    /// Get the number of registered TEEs
+    pub fn tee_count(env: Env) -> u32 {
+        env.storage().instance().get(&TEE_COUNT).unwrap_or(0)
+    }
+
+    /// Get up to limit registered TEEs starting at position start of the
+    /// registry, limit is capped at MAX_PAGE_SIZE
+    pub fn list_tees(env: Env, start: u32, limit: u32) -> Vec<TeeInfo> {
+        tee_page(&env, start, limit)
+    }
+
+    /// Get the TEEs with a status among the page list_tees(start, limit)
+    /// would return, so each call reads a bounded number of entries
+    pub fn list_tees_by_status(env: Env, status: TeeStatus, start: u32, limit: u32) -> Vec<TeeInfo> {
+        let mut tees = Vec::<TeeInfo>::new(&env);
+        for tee_info in tee_page(&env, start, limit).iter() {
+            if tee_info.status == status {
+                tees.push_back(tee_info);
+            }
+        }
+
+        tees
+    }
 }
 
 mod test;
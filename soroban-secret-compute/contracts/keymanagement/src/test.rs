@@ -1,102 +1,176 @@
 #![cfg(test)]
-use crate::{TeeManagement, TeeManagementClient, TeeInfo, TeeStatus};
+use crate::{
+    AttestationReport, Council, CouncilAction, DataKey, Error, PlatformType, StakeConfig, TeeManagement,
+    TeeManagementClient, TeeMetadata, TeeRegistration, TeeStatus, PROPOSAL_EXPIRY, TEE_TTL_EXTEND,
+};
+use ed25519_dalek::{Signer, SigningKey};
 use soroban_sdk::{
-    assert_eq, testutils::Ledger, BytesN, Env,
+    testutils::{Address as _, Ledger}, vec, Address, BytesN, Env, String,
 };
+use mock_staking::{MockStaking, MockStakingClient};
 extern crate std;
 
+/// Register the contract with an admin, mocking all authorizations
+fn setup(env: &Env) -> (TeeManagementClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(TeeManagement, ());
+    let client = TeeManagementClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    client.add_trusted_verifier(&verifier_pubkey(env));
+    (client, admin)
+}
+
+fn verifier_pubkey(env: &Env) -> BytesN<32> {
+    let verifier = SigningKey::from_bytes(&[9u8; 32]);
+    BytesN::from_array(env, &verifier.verifying_key().to_bytes())
+}
+
+fn sign_report(verifier: &SigningKey, report: &AttestationReport) -> BytesN<64> {
+    let mut message = std::vec::Vec::new();
+    message.extend_from_slice(&report.tee_pubkey.to_array());
+    message.extend_from_slice(&report.measurement.to_array());
+    message.extend_from_slice(&(report.platform as u32).to_be_bytes());
+    message.extend_from_slice(&report.expires_at.to_be_bytes());
+    BytesN::from_array(report.tee_pubkey.env(), &verifier.sign(&message).to_bytes())
+}
+
+fn attestation_report(env: &Env, tee_pubkey: &BytesN<32>) -> AttestationReport {
+    AttestationReport {
+        tee_pubkey: tee_pubkey.clone(),
+        measurement: BytesN::from_array(env, &[4u8; 32]),
+        platform: PlatformType::Sgx,
+        expires_at: env.ledger().sequence() + 1000,
+    }
+}
+
+fn tee_metadata(env: &Env) -> TeeMetadata {
+    TeeMetadata {
+        endpoint: String::from_str(env, "https://tee.example.com"),
+        version: String::from_str(env, "1.0.0"),
+        capabilities: 0b11,
+    }
+}
+
+/// Register a TEE operated by the caller with a report signed by the trusted verifier
+fn register_tee(
+    env: &Env,
+    client: &TeeManagementClient,
+    caller: &Address,
+    tee_pubkey: &BytesN<32>,
+) -> bool {
+    let report = attestation_report(env, tee_pubkey);
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    client.register_tee(caller, caller, &report, &verifier_pubkey(env), &signature, &tee_metadata(env))
+}
+
+mod mock_staking {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    /// Staking contract stand-in with settable stakes
+    #[contract]
+    pub struct MockStaking;
+
+    #[contractimpl]
+    impl MockStaking {
+        pub fn set_staked(env: Env, user_addr: Address, amount: i128) {
+            env.storage().persistent().set(&user_addr, &amount);
+        }
+
+        pub fn staked(env: Env, user_addr: Address) -> i128 {
+            env.storage().persistent().get(&user_addr).unwrap_or_default()
+        }
+    }
+}
+
 #[test]
 fn test_register_tee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     // Create a test TEE public key (32 bytes)
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
     // Register TEE
-    let result = client.register_tee(&tee_pubkey);
-    assert_eq!(result, true);
+    let result = register_tee(&env, &client, &admin, &tee_pubkey);
+    assert!(result);
 
     // Verify TEE is registered
     let is_registered = client.is_tee_registered(&tee_pubkey);
-    assert_eq!(is_registered, true);
+    assert!(is_registered);
 
     // Verify TEE is valid (enabled)
     let is_valid = client.is_valid_tee(&tee_pubkey);
-    assert_eq!(is_valid, true);
+    assert!(is_valid);
 
     // Try to register again (should return false)
-    let result2 = client.register_tee(&tee_pubkey);
-    assert_eq!(result2, false);
+    let result2 = register_tee(&env, &client, &admin, &tee_pubkey);
+    assert!(!result2);
 }
 
 #[test]
 fn test_disable_tee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
     // Register TEE
-    client.register_tee(&tee_pubkey);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), true);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    assert!(client.is_valid_tee(&tee_pubkey));
 
     // Disable TEE
-    let result = client.disable_tee(&tee_pubkey);
-    assert_eq!(result, true);
+    let result = client.disable_tee(&admin, &tee_pubkey);
+    assert!(result);
 
     // Verify TEE is still registered but not valid
-    assert_eq!(client.is_tee_registered(&tee_pubkey), true);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), false);
+    assert!(client.is_tee_registered(&tee_pubkey));
+    assert!(!client.is_valid_tee(&tee_pubkey));
 
     // Try to disable non-existent TEE
     let non_existent = BytesN::from_array(&env, &[99u8; 32]);
-    let result2 = client.disable_tee(&non_existent);
-    assert_eq!(result2, false);
+    let result2 = client.disable_tee(&admin, &non_existent);
+    assert!(!result2);
 }
 
 #[test]
 fn test_enable_tee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
     // Register and disable TEE
-    client.register_tee(&tee_pubkey);
-    client.disable_tee(&tee_pubkey);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), false);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    client.disable_tee(&admin, &tee_pubkey);
+    assert!(!client.is_valid_tee(&tee_pubkey));
 
     // Enable TEE
-    let result = client.enable_tee(&tee_pubkey);
-    assert_eq!(result, true);
+    let result = client.enable_tee(&admin, &tee_pubkey);
+    assert!(result);
 
     // Verify TEE is now valid
-    assert_eq!(client.is_valid_tee(&tee_pubkey), true);
+    assert!(client.is_valid_tee(&tee_pubkey));
 
     // Try to enable non-existent TEE
     let non_existent = BytesN::from_array(&env, &[99u8; 32]);
-    let result2 = client.enable_tee(&non_existent);
-    assert_eq!(result2, false);
+    let result2 = client.enable_tee(&admin, &non_existent);
+    assert!(!result2);
 }
 
 #[test]
 fn test_get_tee_info() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
     // Register TEE
-    client.register_tee(&tee_pubkey);
+    register_tee(&env, &client, &admin, &tee_pubkey);
 
     // Get TEE info
     let tee_info = client.get_tee_info(&tee_pubkey);
-    assert_eq!(tee_info.is_some(), true);
+    assert!(tee_info.is_some());
     let info = tee_info.unwrap();
     assert_eq!(info.pubkey, tee_pubkey);
     assert_eq!(info.status, TeeStatus::Enabled);
@@ -104,14 +178,13 @@ fn test_get_tee_info() {
     // Get non-existent TEE info
     let non_existent = BytesN::from_array(&env, &[99u8; 32]);
     let no_info = client.get_tee_info(&non_existent);
-    assert_eq!(no_info.is_none(), true);
+    assert!(no_info.is_none());
 }
 
 #[test]
 fn test_get_all_tees() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     // Initially no TEEs
     let all_tees = client.get_all_tees();
@@ -122,16 +195,16 @@ fn test_get_all_tees() {
     let tee2 = BytesN::from_array(&env, &[2u8; 32]);
     let tee3 = BytesN::from_array(&env, &[3u8; 32]);
 
-    client.register_tee(&tee1);
-    client.register_tee(&tee2);
-    client.register_tee(&tee3);
+    register_tee(&env, &client, &admin, &tee1);
+    register_tee(&env, &client, &admin, &tee2);
+    register_tee(&env, &client, &admin, &tee3);
 
     // Get all TEEs
     let all_tees = client.get_all_tees();
     assert_eq!(all_tees.len(), 3);
 
     // Disable one TEE
-    client.disable_tee(&tee2);
+    client.disable_tee(&admin, &tee2);
 
     // All TEEs should still be in the list
     let all_tees_after_disable = client.get_all_tees();
@@ -139,74 +212,72 @@ fn test_get_all_tees() {
 
     // Verify disabled TEE is still in list but with disabled status
     let tee2_info = client.get_tee_info(&tee2);
-    assert_eq!(tee2_info.is_some(), true);
+    assert!(tee2_info.is_some());
     assert_eq!(tee2_info.unwrap().status, TeeStatus::Disabled);
 }
 
 #[test]
 fn test_tee_status_transitions() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
     // Initially not registered
-    assert_eq!(client.is_tee_registered(&tee_pubkey), false);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), false);
+    assert!(!client.is_tee_registered(&tee_pubkey));
+    assert!(!client.is_valid_tee(&tee_pubkey));
 
     // Register -> should be registered and valid
-    client.register_tee(&tee_pubkey);
-    assert_eq!(client.is_tee_registered(&tee_pubkey), true);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), true);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    assert!(client.is_tee_registered(&tee_pubkey));
+    assert!(client.is_valid_tee(&tee_pubkey));
 
     // Disable -> should be registered but not valid
-    client.disable_tee(&tee_pubkey);
-    assert_eq!(client.is_tee_registered(&tee_pubkey), true);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), false);
+    client.disable_tee(&admin, &tee_pubkey);
+    assert!(client.is_tee_registered(&tee_pubkey));
+    assert!(!client.is_valid_tee(&tee_pubkey));
 
     // Enable -> should be registered and valid again
-    client.enable_tee(&tee_pubkey);
-    assert_eq!(client.is_tee_registered(&tee_pubkey), true);
-    assert_eq!(client.is_valid_tee(&tee_pubkey), true);
+    client.enable_tee(&admin, &tee_pubkey);
+    assert!(client.is_tee_registered(&tee_pubkey));
+    assert!(client.is_valid_tee(&tee_pubkey));
 }
 
 #[test]
 fn test_multiple_tee_operations() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     // Register multiple TEEs
     let tee1 = BytesN::from_array(&env, &[1u8; 32]);
     let tee2 = BytesN::from_array(&env, &[2u8; 32]);
     let tee3 = BytesN::from_array(&env, &[3u8; 32]);
 
-    assert_eq!(client.register_tee(&tee1), true);
-    assert_eq!(client.register_tee(&tee2), true);
-    assert_eq!(client.register_tee(&tee3), true);
+    assert!(register_tee(&env, &client, &admin, &tee1));
+    assert!(register_tee(&env, &client, &admin, &tee2));
+    assert!(register_tee(&env, &client, &admin, &tee3));
 
     // All should be valid
-    assert_eq!(client.is_valid_tee(&tee1), true);
-    assert_eq!(client.is_valid_tee(&tee2), true);
-    assert_eq!(client.is_valid_tee(&tee3), true);
+    assert!(client.is_valid_tee(&tee1));
+    assert!(client.is_valid_tee(&tee2));
+    assert!(client.is_valid_tee(&tee3));
 
     // Disable tee2
-    client.disable_tee(&tee2);
+    client.disable_tee(&admin, &tee2);
 
     // tee1 and tee3 should still be valid, tee2 should not
-    assert_eq!(client.is_valid_tee(&tee1), true);
-    assert_eq!(client.is_valid_tee(&tee2), false);
-    assert_eq!(client.is_valid_tee(&tee3), true);
+    assert!(client.is_valid_tee(&tee1));
+    assert!(!client.is_valid_tee(&tee2));
+    assert!(client.is_valid_tee(&tee3));
 
     // Re-enable tee2
-    client.enable_tee(&tee2);
-    assert_eq!(client.is_valid_tee(&tee2), true);
+    client.enable_tee(&admin, &tee2);
+    assert!(client.is_valid_tee(&tee2));
 
     // Verify all are registered
-    assert_eq!(client.is_tee_registered(&tee1), true);
-    assert_eq!(client.is_tee_registered(&tee2), true);
-    assert_eq!(client.is_tee_registered(&tee3), true);
+    assert!(client.is_tee_registered(&tee1));
+    assert!(client.is_tee_registered(&tee2));
+    assert!(client.is_tee_registered(&tee3));
 }
 
 #[test]
@@ -216,13 +287,12 @@ fn test_tee_info_timestamp() {
         l.timestamp = 1000;
     });
 
-    let contract_id = env.register_contract(None, TeeManagement);
-    let client = TeeManagementClient::new(&env, &contract_id);
+    let (client, admin) = setup(&env);
 
     let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
 
     // Register TEE
-    client.register_tee(&tee_pubkey);
+    register_tee(&env, &client, &admin, &tee_pubkey);
 
     // Get TEE info and verify timestamp
     let tee_info = client.get_tee_info(&tee_pubkey).unwrap();
@@ -230,3 +300,920 @@ fn test_tee_info_timestamp() {
     assert_eq!(tee_info.pubkey, tee_pubkey);
     assert_eq!(tee_info.status, TeeStatus::Enabled);
 }
+
+#[test]
+fn test_admin_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(TeeManagement, ());
+    let client = TeeManagementClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(client.admin(), Some(admin.clone()));
+
+    client.propose_admin(&new_admin);
+    assert_eq!(env.auths()[0].0, admin);
+
+    assert_eq!(client.accept_admin(), new_admin);
+    assert_eq!(env.auths()[0].0, new_admin);
+    assert_eq!(client.admin(), Some(new_admin));
+
+    client.renounce_admin();
+    assert_eq!(client.admin(), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_accept_admin_without_proposal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(TeeManagement, ());
+    let client = TeeManagementClient::new(&env, &contract_id);
+
+    client.initialize(&Address::generate(&env));
+    client.accept_admin();
+}
+
+#[test]
+fn test_registrar_role() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let registrar = Address::generate(&env);
+    assert!(client.add_registrar(&registrar));
+    assert_eq!(env.auths()[0].0, admin);
+    assert!(!client.add_registrar(&registrar));
+    assert!(client.is_registrar(&registrar));
+
+    // Registrars manage the registry with their own authorization
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    assert!(register_tee(&env, &client, &registrar, &tee_pubkey));
+    assert_eq!(env.auths()[0].0, registrar);
+    assert!(client.disable_tee(&registrar, &tee_pubkey));
+    assert!(client.enable_tee(&registrar, &tee_pubkey));
+
+    assert!(client.remove_registrar(&registrar));
+    assert!(!client.is_registrar(&registrar));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_register_tee_unauthorized() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &Address::generate(&env), &tee_pubkey);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_disable_tee_unauthorized() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    client.disable_tee(&Address::generate(&env), &tee_pubkey);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_enable_tee_by_removed_registrar() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let registrar = Address::generate(&env);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    client.add_registrar(&registrar);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    client.disable_tee(&admin, &tee_pubkey);
+    client.remove_registrar(&registrar);
+
+    client.enable_tee(&registrar, &tee_pubkey);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_register_tee_before_initialize() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(TeeManagement, ());
+    let client = TeeManagementClient::new(&env, &contract_id);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &Address::generate(&env), &tee_pubkey);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_register_tee_without_auth() {
+    let env = Env::default();
+    let contract_id = env.register(TeeManagement, ());
+    let client = TeeManagementClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // The admin has not signed the call
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_register_tee_untrusted_verifier() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let untrusted = SigningKey::from_bytes(&[8u8; 32]);
+    let untrusted_pubkey = BytesN::from_array(&env, &untrusted.verifying_key().to_bytes());
+    let report = attestation_report(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let signature = sign_report(&untrusted, &report);
+    client.register_tee(&admin, &admin, &report, &untrusted_pubkey, &signature, &tee_metadata(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_register_tee_expired_report() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let report = attestation_report(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    env.ledger().with_mut(|li| li.sequence_number = report.expires_at);
+    client.register_tee(&admin, &admin, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_register_tee_tampered_report() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    // The measurement no longer matches what the verifier signed
+    let mut report = attestation_report(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    report.measurement = BytesN::from_array(&env, &[5u8; 32]);
+    client.register_tee(&admin, &admin, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+}
+
+#[test]
+fn test_tee_metadata() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let operator = Address::generate(&env);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    let report = AttestationReport {
+        tee_pubkey: tee_pubkey.clone(),
+        measurement: BytesN::from_array(&env, &[4u8; 32]),
+        platform: PlatformType::Tdx,
+        expires_at: 1000,
+    };
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    client.register_tee(&admin, &operator, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+
+    let info = client.get_tee_info(&tee_pubkey).unwrap();
+    assert_eq!(info.operator, operator);
+    assert_eq!(info.measurement, report.measurement);
+    assert_eq!(info.platform, PlatformType::Tdx);
+    assert_eq!(info.endpoint, String::from_str(&env, "https://tee.example.com"));
+    assert_eq!(info.capabilities, 0b11);
+
+    // The operator updates its own metadata
+    let metadata = TeeMetadata {
+        endpoint: String::from_str(&env, "https://tee2.example.com"),
+        version: String::from_str(&env, "1.1.0"),
+        capabilities: 0b111,
+    };
+    client.update_tee_metadata(&tee_pubkey, &metadata);
+    assert_eq!(env.auths()[0].0, operator);
+
+    let info = client.get_tee_info(&tee_pubkey).unwrap();
+    assert_eq!(info.endpoint, metadata.endpoint);
+    assert_eq!(info.version, metadata.version);
+    assert_eq!(info.capabilities, 0b111);
+    assert_eq!(info.measurement, report.measurement);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_update_tee_metadata_without_operator_auth() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+
+    // Only the admin's signature is available
+    env.mock_auths(&[]);
+    client.update_tee_metadata(&tee_pubkey, &tee_metadata(&env));
+}
+
+fn sign_heartbeat(tee_key: &SigningKey, tee_pubkey: &BytesN<32>, last_seen: u32) -> BytesN<64> {
+    let mut message = std::vec::Vec::new();
+    message.extend_from_slice(&tee_pubkey.to_array());
+    message.extend_from_slice(&last_seen.to_be_bytes());
+    BytesN::from_array(tee_pubkey.env(), &tee_key.sign(&message).to_bytes())
+}
+
+#[test]
+fn test_heartbeat_liveness() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_liveness_window(&100);
+
+    let tee_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &tee_key.verifying_key().to_bytes());
+    env.ledger().with_mut(|li| li.sequence_number = 10);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().last_seen, 10);
+
+    // Still valid at the end of the window
+    env.ledger().with_mut(|li| li.sequence_number = 110);
+    assert!(client.is_valid_tee(&tee_pubkey));
+    assert_eq!(client.get_stale_tees(&0, &10).len(), 0);
+
+    // Stale once the window has passed
+    env.ledger().with_mut(|li| li.sequence_number = 111);
+    assert!(!client.is_valid_tee(&tee_pubkey));
+    assert_eq!(client.get_stale_tees(&0, &10).len(), 1);
+    assert_eq!(client.get_stale_tees(&1, &10).len(), 0);
+
+    // A heartbeat makes it valid again
+    client.heartbeat(&tee_pubkey, &sign_heartbeat(&tee_key, &tee_pubkey, 10));
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().last_seen, 111);
+    assert!(client.is_valid_tee(&tee_pubkey));
+    assert_eq!(client.get_stale_tees(&0, &10).len(), 0);
+
+    // Liveness checks can be switched off
+    env.ledger().with_mut(|li| li.sequence_number = 500);
+    assert!(!client.is_valid_tee(&tee_pubkey));
+    client.set_liveness_window(&0);
+    assert!(client.is_valid_tee(&tee_pubkey));
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_heartbeat_replay() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let tee_key = SigningKey::from_bytes(&[7u8; 32]);
+    let tee_pubkey = BytesN::from_array(&env, &tee_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &admin, &tee_pubkey);
+
+    let signature = sign_heartbeat(&tee_key, &tee_pubkey, 0);
+    env.ledger().with_mut(|li| li.sequence_number = 5);
+    client.heartbeat(&tee_pubkey, &signature);
+
+    // The same heartbeat cannot be submitted again
+    env.ledger().with_mut(|li| li.sequence_number = 6);
+    client.heartbeat(&tee_pubkey, &signature);
+}
+
+#[test]
+fn test_registration_expiry_and_renewal() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    client.set_liveness_window(&0);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    let info = client.get_tee_info(&tee_pubkey).unwrap();
+    assert_eq!(info.valid_until, 1000);
+
+    // The registration expires with its attestation report
+    env.ledger().with_mut(|li| li.sequence_number = 999);
+    assert!(client.is_valid_tee(&tee_pubkey));
+    env.ledger().with_mut(|li| li.sequence_number = 1000);
+    assert!(!client.is_valid_tee(&tee_pubkey));
+
+    // A fresh report with a new TCB level renews it
+    let report = AttestationReport {
+        tee_pubkey: tee_pubkey.clone(),
+        measurement: BytesN::from_array(&env, &[6u8; 32]),
+        platform: PlatformType::Sgx,
+        expires_at: 3000,
+    };
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    client.renew_tee(&report, &verifier_pubkey(&env), &signature);
+    assert_eq!(env.auths()[0].0, admin);
+
+    let info = client.get_tee_info(&tee_pubkey).unwrap();
+    assert_eq!(info.valid_until, 3000);
+    assert_eq!(info.measurement, report.measurement);
+    assert!(client.is_valid_tee(&tee_pubkey));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_renew_unregistered_tee() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let report = attestation_report(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    client.renew_tee(&report, &verifier_pubkey(&env), &signature);
+}
+
+fn sign_key_rotation(key: &SigningKey, old_pubkey: &BytesN<32>, new_pubkey: &BytesN<32>) -> BytesN<64> {
+    let mut message = std::vec::Vec::new();
+    message.extend_from_slice(&old_pubkey.to_array());
+    message.extend_from_slice(&new_pubkey.to_array());
+    BytesN::from_array(old_pubkey.env(), &key.sign(&message).to_bytes())
+}
+
+/// Rotate old_key to new_key with a fresh report for the new key signed by
+/// the trusted verifier
+fn rotate_tee_key(
+    env: &Env,
+    client: &TeeManagementClient,
+    old_key: &SigningKey,
+    new_key: &SigningKey,
+    report: &AttestationReport,
+) {
+    let old_pubkey = BytesN::from_array(env, &old_key.verifying_key().to_bytes());
+    let new_pubkey = BytesN::from_array(env, &new_key.verifying_key().to_bytes());
+    client.rotate_tee_key(
+        &old_pubkey,
+        report,
+        &verifier_pubkey(env),
+        &sign_report(&SigningKey::from_bytes(&[9u8; 32]), report),
+        &sign_key_rotation(old_key, &old_pubkey, &new_pubkey),
+        &sign_key_rotation(new_key, &old_pubkey, &new_pubkey),
+    );
+}
+
+#[test]
+fn test_rotate_tee_key() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let old_key = SigningKey::from_bytes(&[7u8; 32]);
+    let new_key = SigningKey::from_bytes(&[8u8; 32]);
+    let old_pubkey = BytesN::from_array(&env, &old_key.verifying_key().to_bytes());
+    let new_pubkey = BytesN::from_array(&env, &new_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &admin, &old_pubkey);
+    let old_info = client.get_tee_info(&old_pubkey).unwrap();
+
+    let mut report = attestation_report(&env, &new_pubkey);
+    report.measurement = BytesN::from_array(&env, &[5u8; 32]);
+    report.expires_at = env.ledger().sequence() + 2000;
+    rotate_tee_key(&env, &client, &old_key, &new_key, &report);
+    assert_eq!(env.auths()[0].0, admin);
+
+    // The registration moved to the new key
+    let new_info = client.get_tee_info(&new_pubkey).unwrap();
+    assert_eq!(new_info.pubkey, new_pubkey);
+    assert_eq!(new_info.operator, old_info.operator);
+    assert_eq!(new_info.registered_at, old_info.registered_at);
+    assert_eq!(new_info.last_seen, old_info.last_seen);
+
+    // The new key carries the measurement and expiry of its own report
+    assert_eq!(new_info.measurement, report.measurement);
+    assert_eq!(new_info.valid_until, report.expires_at);
+    assert!(client.is_valid_tee(&new_pubkey));
+
+    // The old key is tombstoned
+    assert!(!client.is_tee_registered(&old_pubkey));
+    assert_eq!(client.rotated_key(&old_pubkey), Some(new_pubkey));
+    let report = attestation_report(&env, &old_pubkey);
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    let result = client.try_register_tee(&admin, &admin, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+    assert_eq!(result, Err(Ok(Error::KeyRotated.into())));
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_rotate_tee_key_without_new_key_signature() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let old_key = SigningKey::from_bytes(&[7u8; 32]);
+    let new_key = SigningKey::from_bytes(&[8u8; 32]);
+    let old_pubkey = BytesN::from_array(&env, &old_key.verifying_key().to_bytes());
+    let new_pubkey = BytesN::from_array(&env, &new_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &admin, &old_pubkey);
+
+    // The old key cannot move the registration to a key it does not control
+    let report = attestation_report(&env, &new_pubkey);
+    let sig_by_old = sign_key_rotation(&old_key, &old_pubkey, &new_pubkey);
+    client.rotate_tee_key(
+        &old_pubkey,
+        &report,
+        &verifier_pubkey(&env),
+        &sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report),
+        &sig_by_old,
+        &sig_by_old,
+    );
+}
+
+#[test]
+fn test_rotate_tee_key_untrusted_report() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let old_key = SigningKey::from_bytes(&[7u8; 32]);
+    let new_key = SigningKey::from_bytes(&[8u8; 32]);
+    let old_pubkey = BytesN::from_array(&env, &old_key.verifying_key().to_bytes());
+    let new_pubkey = BytesN::from_array(&env, &new_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &admin, &old_pubkey);
+
+    // The new key needs its own report from a trusted verifier
+    let report = attestation_report(&env, &new_pubkey);
+    let rogue_verifier = SigningKey::from_bytes(&[10u8; 32]);
+    let result = client.try_rotate_tee_key(
+        &old_pubkey,
+        &report,
+        &BytesN::from_array(&env, &rogue_verifier.verifying_key().to_bytes()),
+        &sign_report(&rogue_verifier, &report),
+        &sign_key_rotation(&old_key, &old_pubkey, &new_pubkey),
+        &sign_key_rotation(&new_key, &old_pubkey, &new_pubkey),
+    );
+    assert_eq!(result, Err(Ok(Error::UntrustedVerifier.into())));
+
+    // An expired report is rejected as well
+    let mut report = attestation_report(&env, &new_pubkey);
+    report.expires_at = env.ledger().sequence();
+    let result = client.try_rotate_tee_key(
+        &old_pubkey,
+        &report,
+        &verifier_pubkey(&env),
+        &sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report),
+        &sign_key_rotation(&old_key, &old_pubkey, &new_pubkey),
+        &sign_key_rotation(&new_key, &old_pubkey, &new_pubkey),
+    );
+    assert_eq!(result, Err(Ok(Error::ReportExpired.into())));
+    assert!(client.is_tee_registered(&old_pubkey));
+}
+
+#[test]
+fn test_list_tees() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    assert_eq!(client.tee_count(), 0);
+
+    for i in 1..=5u8 {
+        register_tee(&env, &client, &admin, &BytesN::from_array(&env, &[i; 32]));
+    }
+    client.disable_tee(&admin, &BytesN::from_array(&env, &[2u8; 32]));
+    client.disable_tee(&admin, &BytesN::from_array(&env, &[4u8; 32]));
+    assert_eq!(client.tee_count(), 5);
+
+    let page = client.list_tees(&0, &2);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().pubkey, BytesN::from_array(&env, &[1u8; 32]));
+    assert_eq!(page.get(1).unwrap().pubkey, BytesN::from_array(&env, &[2u8; 32]));
+    assert_eq!(client.list_tees(&4, &2).len(), 1);
+    assert_eq!(client.list_tees(&5, &2).len(), 0);
+
+    let disabled = client.list_tees_by_status(&TeeStatus::Disabled, &0, &5);
+    assert_eq!(disabled.len(), 2);
+    assert_eq!(disabled.get(1).unwrap().pubkey, BytesN::from_array(&env, &[4u8; 32]));
+    assert_eq!(client.list_tees_by_status(&TeeStatus::Enabled, &0, &2).len(), 1);
+}
+
+#[test]
+fn test_registry_entries_extended_on_access() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+
+    // Keep the contract instance live; only the registry entries are tested
+    env.as_contract(&client.address, || {
+        env.storage().instance().extend_ttl(3 * TEE_TTL_EXTEND, 3 * TEE_TTL_EXTEND);
+    });
+
+    // Reading the TEE close to its expiry keeps it alive
+    env.ledger().with_mut(|li| li.sequence_number = TEE_TTL_EXTEND - 1000);
+    assert!(client.get_tee_info(&tee_pubkey).is_some());
+    assert_eq!(client.list_tees(&0, &10).len(), 1);
+
+    // Well past the TTL it was registered with
+    env.ledger().with_mut(|li| li.sequence_number = 2 * TEE_TTL_EXTEND - 2000);
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().pubkey, tee_pubkey);
+    assert_eq!(client.list_tees(&0, &10).len(), 1);
+}
+
+#[test]
+fn test_list_tees_after_rotation() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let old_key = SigningKey::from_bytes(&[7u8; 32]);
+    let new_key = SigningKey::from_bytes(&[8u8; 32]);
+    let old_pubkey = BytesN::from_array(&env, &old_key.verifying_key().to_bytes());
+    let new_pubkey = BytesN::from_array(&env, &new_key.verifying_key().to_bytes());
+    register_tee(&env, &client, &admin, &old_pubkey);
+    register_tee(&env, &client, &admin, &BytesN::from_array(&env, &[1u8; 32]));
+
+    rotate_tee_key(&env, &client, &old_key, &new_key, &attestation_report(&env, &new_pubkey));
+
+    // The last TEE moves into the freed position and the new key is appended
+    assert_eq!(client.tee_count(), 2);
+    let tees = client.list_tees(&0, &10);
+    assert_eq!(tees.get(0).unwrap().pubkey, BytesN::from_array(&env, &[1u8; 32]));
+    assert_eq!(tees.get(1).unwrap().pubkey, new_pubkey);
+    assert_eq!(client.get_all_tees(), tees);
+}
+
+/// Set up a 2-of-3 council
+fn setup_council(env: &Env, client: &TeeManagementClient) -> (Address, Address, Address) {
+    let members = (Address::generate(env), Address::generate(env), Address::generate(env));
+    client.set_council(&vec![env, members.0.clone(), members.1.clone(), members.2.clone()], &2);
+    members
+}
+
+/// Build a council registration of a TEE operated by operator
+fn registration_action(env: &Env, operator: &Address, tee_pubkey: &BytesN<32>) -> CouncilAction {
+    let report = attestation_report(env, tee_pubkey);
+    CouncilAction::RegisterTee(TeeRegistration {
+        operator: operator.clone(),
+        signature: sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report),
+        report,
+        verifier: verifier_pubkey(env),
+        metadata: tee_metadata(env),
+    })
+}
+
+#[test]
+fn test_council_register_tee() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (alice, bob, _) = setup_council(&env, &client);
+
+    let operator = Address::generate(&env);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    let action = registration_action(&env, &operator, &tee_pubkey);
+
+    let proposal_id = client.propose_action(&alice, &action);
+    assert_eq!(client.proposal(&proposal_id).unwrap().approvals.len(), 1);
+
+    // One approval is not enough
+    assert!(client.try_execute_action(&proposal_id).is_err());
+    assert!(!client.is_tee_registered(&tee_pubkey));
+
+    assert_eq!(client.approve_action(&bob, &proposal_id), 2);
+    assert!(client.execute_action(&proposal_id));
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().operator, operator);
+    assert!(client.proposal(&proposal_id).unwrap().executed);
+
+    // A proposal only runs once
+    let result = client.try_execute_action(&proposal_id);
+    assert_eq!(result, Err(Ok(Error::ProposalExecuted.into())));
+}
+
+#[test]
+fn test_council_measurement_allowlist() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (alice, bob, _) = setup_council(&env, &client);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+
+    // Allow a measurement other than the one in the test reports
+    let measurement = BytesN::from_array(&env, &[5u8; 32]);
+    let proposal_id = client.propose_action(&alice, &CouncilAction::AllowMeasurement(measurement.clone()));
+    client.approve_action(&bob, &proposal_id);
+    assert!(client.execute_action(&proposal_id));
+    assert!(client.is_measurement_allowed(&measurement));
+    assert_eq!(client.allowed_measurement_count(), 1);
+
+    // Reports with other measurements are now refused
+    let proposal_id = client.propose_action(&alice, &registration_action(&env, &admin, &tee_pubkey));
+    client.approve_action(&bob, &proposal_id);
+    let result = client.try_execute_action(&proposal_id);
+    assert_eq!(result, Err(Ok(Error::MeasurementNotAllowed.into())));
+
+    // The last measurement cannot be disallowed, which would accept any
+    let disallow = CouncilAction::DisallowMeasurement(measurement.clone());
+    let proposal_id = client.propose_action(&bob, &disallow);
+    client.approve_action(&alice, &proposal_id);
+    let result = client.try_execute_action(&proposal_id);
+    assert_eq!(result, Err(Ok(Error::LastAllowedMeasurement.into())));
+    assert!(client.is_measurement_allowed(&measurement));
+
+    // It is replaced by allowing the new measurement first
+    let reported = BytesN::from_array(&env, &[4u8; 32]);
+    let proposal_id = client.propose_action(&alice, &CouncilAction::AllowMeasurement(reported.clone()));
+    client.approve_action(&bob, &proposal_id);
+    assert!(client.execute_action(&proposal_id));
+    let proposal_id = client.propose_action(&bob, &disallow);
+    client.approve_action(&alice, &proposal_id);
+    assert!(client.execute_action(&proposal_id));
+    assert_eq!(client.allowed_measurement_count(), 1);
+    assert!(client.is_measurement_allowed(&reported));
+
+    let proposal_id = client.propose_action(&alice, &registration_action(&env, &admin, &tee_pubkey));
+    client.approve_action(&bob, &proposal_id);
+    assert!(client.execute_action(&proposal_id));
+}
+
+#[test]
+fn test_council_disable_enable() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    let (alice, _, carol) = setup_council(&env, &client);
+
+    let proposal_id = client.propose_action(&carol, &CouncilAction::DisableTee(tee_pubkey.clone()));
+    client.approve_action(&alice, &proposal_id);
+    client.execute_action(&proposal_id);
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().status, TeeStatus::Disabled);
+
+    let proposal_id = client.propose_action(&alice, &CouncilAction::EnableTee(tee_pubkey.clone()));
+    client.approve_action(&carol, &proposal_id);
+    client.execute_action(&proposal_id);
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().status, TeeStatus::Enabled);
+}
+
+#[test]
+fn test_council_proposal_errors() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (alice, bob, _) = setup_council(&env, &client);
+
+    let action = CouncilAction::DisableTee(BytesN::from_array(&env, &[1u8; 32]));
+    let outsider = Address::generate(&env);
+    let result = client.try_propose_action(&outsider, &action);
+    assert_eq!(result, Err(Ok(Error::NotCouncilMember.into())));
+
+    let proposal_id = client.propose_action(&alice, &action);
+    let result = client.try_approve_action(&alice, &proposal_id);
+    assert_eq!(result, Err(Ok(Error::AlreadyApproved.into())));
+    let result = client.try_approve_action(&bob, &7);
+    assert_eq!(result, Err(Ok(Error::ProposalNotFound.into())));
+
+    // Proposals expire; keep the contract storage live past the expiry
+    env.ledger().with_mut(|li| li.max_entry_ttl = 20_000);
+    env.as_contract(&client.address, || {
+        env.storage().instance().extend_ttl(20_000, 20_000);
+        env.storage().persistent().extend_ttl(&DataKey::Proposal(proposal_id), 20_000, 20_000);
+    });
+    env.ledger().with_mut(|li| li.sequence_number = PROPOSAL_EXPIRY + 1);
+    let result = client.try_approve_action(&bob, &proposal_id);
+    assert_eq!(result, Err(Ok(Error::ProposalExpired.into())));
+}
+
+#[test]
+fn test_council_governs_registry() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let registrar = Address::generate(&env);
+    client.add_registrar(&registrar);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    setup_council(&env, &client);
+
+    // Neither the admin nor a registrar can change the registry directly
+    let report = attestation_report(&env, &BytesN::from_array(&env, &[2u8; 32]));
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    let result = client.try_register_tee(&registrar, &registrar, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+    let result = client.try_disable_tee(&admin, &tee_pubkey);
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+    let result = client.try_enable_tee(&registrar, &tee_pubkey);
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+
+    // Nor can the admin replace the council
+    let result = client.try_set_council(&vec![&env, admin.clone()], &1);
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+}
+
+#[test]
+fn test_council_governs_configuration() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (alice, bob, _) = setup_council(&env, &client);
+    let verifier = BytesN::from_array(&env, &[7u8; 32]);
+    let staking = env.register(MockStaking, ());
+
+    // The admin can no longer change the configuration directly
+    let result = client.try_add_trusted_verifier(&verifier);
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+    let result = client.try_remove_trusted_verifier(&verifier_pubkey(&env));
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+    let result = client.try_set_liveness_window(&0);
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+    let result = client.try_set_stake_config(&staking, &1000);
+    assert_eq!(result, Err(Ok(Error::CouncilGoverned.into())));
+
+    // The council changes it through proposals instead
+    let stake_config = StakeConfig {
+        staking_contract: staking.clone(),
+        min_bond: 1000,
+    };
+    let actions = [
+        CouncilAction::AddTrustedVerifier(verifier.clone()),
+        CouncilAction::RemoveTrustedVerifier(verifier_pubkey(&env)),
+        CouncilAction::SetLivenessWindow(0),
+        CouncilAction::SetStakeConfig(stake_config.clone()),
+    ];
+    for action in actions {
+        let proposal_id = client.propose_action(&alice, &action);
+        client.approve_action(&bob, &proposal_id);
+        assert!(client.execute_action(&proposal_id));
+    }
+    assert!(client.is_trusted_verifier(&verifier));
+    assert!(!client.is_trusted_verifier(&verifier_pubkey(&env)));
+    assert_eq!(client.liveness_window(), 0);
+    assert_eq!(client.stake_config(), Some(stake_config));
+
+    // The stake configuration is still checked
+    let action = CouncilAction::SetStakeConfig(StakeConfig {
+        staking_contract: staking,
+        min_bond: 0,
+    });
+    let proposal_id = client.propose_action(&alice, &action);
+    client.approve_action(&bob, &proposal_id);
+    let result = client.try_execute_action(&proposal_id);
+    assert_eq!(result, Err(Ok(Error::InvalidStakeConfig.into())));
+}
+
+#[test]
+fn test_council_changes_membership() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (alice, bob, carol) = setup_council(&env, &client);
+    client.renounce_admin();
+
+    // The council replaces carol with dave and lowers the threshold
+    let dave = Address::generate(&env);
+    let council = Council {
+        members: vec![&env, alice.clone(), bob.clone(), dave.clone()],
+        threshold: 1,
+    };
+    let proposal_id = client.propose_action(&alice, &CouncilAction::SetCouncil(council.clone()));
+    client.approve_action(&carol, &proposal_id);
+    assert!(client.execute_action(&proposal_id));
+    assert_eq!(client.council(), Some(council));
+
+    let action = CouncilAction::DisableTee(BytesN::from_array(&env, &[1u8; 32]));
+    let result = client.try_propose_action(&carol, &action);
+    assert_eq!(result, Err(Ok(Error::NotCouncilMember.into())));
+    let proposal_id = client.propose_action(&dave, &action);
+    assert!(!client.execute_action(&proposal_id));
+
+    // An invalid council is refused when executed
+    let council = Council {
+        members: vec![&env, dave.clone()],
+        threshold: 2,
+    };
+    let proposal_id = client.propose_action(&dave, &CouncilAction::SetCouncil(council));
+    let result = client.try_execute_action(&proposal_id);
+    assert_eq!(result, Err(Ok(Error::InvalidCouncil.into())));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_set_council_invalid_threshold() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let member = Address::generate(&env);
+    client.set_council(&vec![&env, member], &2);
+}
+
+#[test]
+fn test_revoke_tee() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    env.ledger().with_mut(|li| li.sequence_number = 42);
+
+    assert!(client.revoke_tee(&admin, &tee_pubkey, &3));
+    assert!(!client.revoke_tee(&admin, &tee_pubkey, &4));
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().status, TeeStatus::Revoked);
+    assert!(!client.is_valid_tee(&tee_pubkey));
+
+    let revocation = client.revocation(&tee_pubkey).unwrap();
+    assert_eq!(revocation.revoked_at, 42);
+    assert_eq!(revocation.reason_code, 3);
+
+    // Neither enabling nor disabling nor registering again brings it back
+    let result = client.try_enable_tee(&admin, &tee_pubkey);
+    assert_eq!(result, Err(Ok(Error::TeeRevoked.into())));
+    let result = client.try_disable_tee(&admin, &tee_pubkey);
+    assert_eq!(result, Err(Ok(Error::TeeRevoked.into())));
+    let report = attestation_report(&env, &tee_pubkey);
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    let result = client.try_register_tee(&admin, &admin, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+    assert_eq!(result, Err(Ok(Error::TeeRevoked.into())));
+
+    assert_eq!(client.list_tees_by_status(&TeeStatus::Revoked, &0, &10).len(), 1);
+}
+
+#[test]
+fn test_council_revoke_tee() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    let (alice, bob, _) = setup_council(&env, &client);
+    env.ledger().with_mut(|li| li.sequence_number = 42);
+
+    let action = CouncilAction::RevokeTee(tee_pubkey.clone(), 3);
+    let proposal_id = client.propose_action(&alice, &action);
+    client.approve_action(&bob, &proposal_id);
+    assert!(client.execute_action(&proposal_id));
+    assert_eq!(client.get_tee_info(&tee_pubkey).unwrap().status, TeeStatus::Revoked);
+    assert_eq!(client.revocation(&tee_pubkey).unwrap().reason_code, 3);
+
+    // Revoking again has no effect
+    let proposal_id = client.propose_action(&bob, &action);
+    client.approve_action(&alice, &proposal_id);
+    assert!(!client.execute_action(&proposal_id));
+    assert_eq!(client.revocation(&tee_pubkey).unwrap().revoked_at, 42);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_revoke_tee_unauthorized() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    register_tee(&env, &client, &admin, &tee_pubkey);
+    client.revoke_tee(&Address::generate(&env), &tee_pubkey, &3);
+}
+
+#[test]
+fn test_registration_requires_bond() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let staking = env.register(MockStaking, ());
+    let staking_client = MockStakingClient::new(&env, &staking);
+    client.set_stake_config(&staking, &1000);
+
+    // An operator without the minimum bond cannot register a TEE
+    let tee_pubkey = BytesN::from_array(&env, &[1u8; 32]);
+    staking_client.set_staked(&admin, &999);
+    let report = attestation_report(&env, &tee_pubkey);
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    let result = client.try_register_tee(&admin, &admin, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+    assert_eq!(result, Err(Ok(Error::InsufficientStake.into())));
+
+    staking_client.set_staked(&admin, &1000);
+    assert!(register_tee(&env, &client, &admin, &tee_pubkey));
+    assert!(client.is_valid_tee(&tee_pubkey));
+
+    // The TEE stops being valid once its operator unstakes below the bond
+    staking_client.set_staked(&admin, &500);
+    assert!(!client.is_valid_tee(&tee_pubkey));
+}
+
+#[test]
+fn test_bond_scales_with_operator_tees() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let staking = env.register(MockStaking, ());
+    let staking_client = MockStakingClient::new(&env, &staking);
+    client.set_stake_config(&staking, &1000);
+    staking_client.set_staked(&admin, &1999);
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    let second = BytesN::from_array(&env, &[2u8; 32]);
+    assert!(register_tee(&env, &client, &admin, &first));
+    assert_eq!(client.operator_tee_count(&admin), 1);
+
+    // A second TEE needs a second bond
+    let report = attestation_report(&env, &second);
+    let signature = sign_report(&SigningKey::from_bytes(&[9u8; 32]), &report);
+    let result = client.try_register_tee(&admin, &admin, &report, &verifier_pubkey(&env), &signature, &tee_metadata(&env));
+    assert_eq!(result, Err(Ok(Error::InsufficientStake.into())));
+
+    staking_client.set_staked(&admin, &2000);
+    assert!(register_tee(&env, &client, &admin, &second));
+    assert_eq!(client.operator_tee_count(&admin), 2);
+
+    // Unstaking below the total bond invalidates all of the operator's TEEs
+    staking_client.set_staked(&admin, &1500);
+    assert!(!client.is_valid_tee(&first));
+    assert!(!client.is_valid_tee(&second));
+
+    // Revoked TEEs no longer need a bond
+    client.revoke_tee(&admin, &second, &1);
+    assert_eq!(client.operator_tee_count(&admin), 1);
+    assert!(client.is_valid_tee(&first));
+}
+
+#[test]
+fn test_set_stake_config_requires_positive_bond() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let staking = env.register(MockStaking, ());
+    let result = client.try_set_stake_config(&staking, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidStakeConfig.into())));
+    let result = client.try_set_stake_config(&staking, &-1);
+    assert_eq!(result, Err(Ok(Error::InvalidStakeConfig.into())));
+    assert_eq!(client.stake_config(), None);
+}
@@ -0,0 +1,128 @@
+//! Two-step admin transfer shared by all contracts
+//!
+//! The current admin proposes a successor, who must accept before the
+//! proposal expires. The admin can also renounce the role for good.
+//! Each contract keeps its admin under its own storage key and passes it in.
+
+use soroban_sdk::{
+    contracterror, contracttype, panic_with_error, symbol_short, Address, Env, IntoVal, Symbol,
+    Val,
+};
+
+const PENDING_ADMIN: Symbol = symbol_short!("PENDADMIN");
+const ADMIN_RENOUNCED: Symbol = symbol_short!("RENOUNCED");
+
+/// Number of ledgers a proposed admin has to accept the transfer (about one day)
+pub const ADMIN_TRANSFER_EXPIRY: u32 = 17_280;
+
+/// Admin transfer waiting for the new admin to accept
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingAdmin {
+    pub admin: Address,
+    pub expires_at: u32,
+}
+
+/// Codes start at 100 so they don't collide with the contracts' own errors
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AdminError {
+    AdminNotSet = 100,
+    NoPendingAdmin = 101,
+    PendingAdminExpired = 102,
+}
+
+fn current_admin<K: IntoVal<Env, Val>>(env: &Env, admin_key: &K) -> Address {
+    match env.storage().instance().get(admin_key) {
+        Some(admin) => admin,
+        None => panic_with_error!(env, AdminError::AdminNotSet),
+    }
+}
+
+/// Get the admin transfer waiting to be accepted
+pub fn pending_admin(env: &Env) -> Option<PendingAdmin> {
+    env.storage().instance().get(&PENDING_ADMIN)
+}
+
+/// Check if the admin role has been renounced
+/// Contracts must refuse to initialize a new admin once it has
+pub fn admin_renounced(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&ADMIN_RENOUNCED)
+        .unwrap_or(false)
+}
+
+/// Propose a new admin, replacing any pending proposal
+/// Requires the current admin's authorization
+pub fn propose_admin<K: IntoVal<Env, Val>>(
+    env: &Env,
+    admin_key: &K,
+    new_admin: Address,
+) -> PendingAdmin {
+    let admin = current_admin(env, admin_key);
+    admin.require_auth();
+
+    let pending = PendingAdmin {
+        admin: new_admin.clone(),
+        expires_at: env.ledger().sequence().saturating_add(ADMIN_TRANSFER_EXPIRY),
+    };
+    env.storage().instance().set(&PENDING_ADMIN, &pending);
+    env.storage().instance().extend_ttl(100, 100);
+
+    // Emit event
+    let admin_val: Val = admin.into_val(env);
+    let new_admin_val: Val = new_admin.into_val(env);
+    let expires_at_val: Val = pending.expires_at.into_val(env);
+    env.events().publish(
+        ("ADMIN_PROPOSED",),
+        (admin_val, new_admin_val, expires_at_val),
+    );
+
+    pending
+}
+
+/// Complete a pending transfer, returns the new admin
+/// Requires the proposed admin's authorization before the proposal expires
+pub fn accept_admin<K: IntoVal<Env, Val>>(env: &Env, admin_key: &K) -> Address {
+    let pending = match pending_admin(env) {
+        Some(pending) => pending,
+        None => panic_with_error!(env, AdminError::NoPendingAdmin),
+    };
+    if env.ledger().sequence() > pending.expires_at {
+        panic_with_error!(env, AdminError::PendingAdminExpired);
+    }
+    pending.admin.require_auth();
+
+    let previous_admin = current_admin(env, admin_key);
+    env.storage().instance().set(admin_key, &pending.admin);
+    env.storage().instance().remove(&PENDING_ADMIN);
+    env.storage().instance().extend_ttl(100, 100);
+
+    // Emit event
+    let previous_admin_val: Val = previous_admin.into_val(env);
+    let new_admin_val: Val = pending.admin.clone().into_val(env);
+    env.events().publish(
+        ("ADMIN_TRANSFERRED",),
+        (previous_admin_val, new_admin_val),
+    );
+
+    pending.admin
+}
+
+/// Give up the admin role permanently, dropping any pending transfer
+/// Requires the current admin's authorization
+pub fn renounce_admin<K: IntoVal<Env, Val>>(env: &Env, admin_key: &K) {
+    let admin = current_admin(env, admin_key);
+    admin.require_auth();
+
+    env.storage().instance().remove(admin_key);
+    env.storage().instance().remove(&PENDING_ADMIN);
+    env.storage().instance().set(&ADMIN_RENOUNCED, &true);
+    env.storage().instance().extend_ttl(100, 100);
+
+    // Emit event
+    let admin_val: Val = admin.into_val(env);
+    env.events().publish(("ADMIN_RENOUNCED",), (admin_val,));
+}
@@ -1,6 +1,8 @@
 #![no_std]
 
-use soroban_sdk::{contracttype, String};
+use soroban_sdk::contracttype;
+
+pub mod admin;
 
 #[contracttype]
 #[derive(Debug, Clone, PartialEq)]